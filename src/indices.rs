@@ -1,4 +1,4 @@
-use crate::Indices;
+use crate::{ranktree::Fenwick, vecops::Comoments, Indices};
 
 impl Indices for &[usize] {
     /// Inverts an index, eg. from sort index to ranks.
@@ -20,6 +20,12 @@ impl Indices for &[usize] {
         self.iter().map(|&sub| v[sub].clone()).collect()
     }
 
+    /// Like `select` but collects only references to the items of `v`.
+    /// Suitable for bulky end types, which are not cloned.
+    fn select_refs<T>(self, v: &[T]) -> Vec<&T> {
+        self.iter().map(|&sub| &v[sub]).collect()
+    }
+
     /// Given a complete (sort) index, extracts indicated values from `v`.
     /// When ascending is false, collects in the reverse order.  
     /// Used by msort for ascending or descending sort.   
@@ -34,6 +40,17 @@ impl Indices for &[usize] {
         }
     }
 
+    /// Given a complete (sort) index, collects references to the indicated items of `v`.
+    /// When ascending is false, collects in the reverse order.
+    /// Zero-clone version of `unindex`, for bulky end types.
+    fn unindex_refs<T>(self, v: &[T], ascending: bool) -> Vec<&T> {
+        if ascending {
+            self.iter().map(|&i| &v[i]).collect()
+        } else {
+            self.iter().rev().map(|&i| &v[i]).collect()
+        }
+    }
+
     /// Complement of an index  (is symmetric) -
     /// .complindex() toggles rank index between ascending/descending.
     /// To toggle sort index between ascending/descending, use the general reversal `revs`:
@@ -52,26 +69,89 @@ impl Indices for &[usize] {
     /// of the original data. However, in general, any other ordinal measures
     /// could be deployed (not just the ranks).
     fn ucorrelation(self, v: &[usize]) -> f64 {
-        let (mut sy, mut sxy, mut sx2, mut sy2) = (0_f64, 0_f64, 0_f64, 0_f64);
-        let sx: f64 = self
-            .iter()
-            .zip(v)
-            .map(|(&ux, &uy)| {
-                let x = ux as f64;
-                let y = uy as f64;
-                sy += y;
-                sxy += x * y;
-                sx2 += x * x;
-                sy2 += y * y;
-                x
-            })
-            .sum();
-        let nf = self.len() as f64;
-        (sxy - sx / nf * sy) / ((sx2 - sx / nf * sx) * (sy2 - sy / nf * sy)).sqrt()
+        // numerically stable accumulation, as in `Vecops::correlation`
+        let mut m = Comoments::default();
+        for (&ux, &uy) in self.iter().zip(v) {
+            m.push(ux as f64, uy as f64, 1.0);
+        }
+        m.correlation()
     }
 
     /// Potentially useful clone-recast of &[usize] to Vec<f64>
     fn indx_to_f64(self) -> Vec<f64> {
         self.iter().map(|&x| x as f64).collect()
     }
+
+    /// Lehmer code of a permutation of `0..n`: the i-th digit is the number of
+    /// items following the i-th item that are lesser than it. Counted from the back
+    /// by a Fenwick tree, in O(n log n). The digits are those of the
+    /// permutation's lexicographic rank in the factorial number system.
+    /// Panics when self is not a permutation: an item out of range or repeated.
+    fn lehmer(self) -> Vec<usize> {
+        let mut seen = Fenwick::new(self.len());
+        let mut code = vec![0_usize; self.len()];
+        for (i, &p) in self.iter().enumerate().rev() {
+            assert!(p < self.len(), "lehmer: {p} is not a permutation item");
+            code[i] = seen.prefix(p);
+            assert!(seen.prefix(p + 1) == code[i], "lehmer: {p} is repeated");
+            seen.insert(p);
+        }
+        code
+    }
+
+    /// Decodes Lehmer code into its permutation of `0..n`:
+    /// each digit picks the digit-th (from 0) of the items still unused.
+    /// O(n log n) by the binary descent of a Fenwick tree.
+    fn from_lehmer(code: &[usize]) -> Vec<usize> {
+        let n = code.len();
+        let mut unused = Fenwick::from_counts(&vec![1_usize; n]);
+        code.iter()
+            .enumerate()
+            .map(|(i, &d)| {
+                assert!(d < n - i, "from_lehmer: digit {d} at {i} out of range");
+                let p = unused.kth(d);
+                unused.remove(p);
+                p
+            })
+            .collect()
+    }
+
+    /// Lexicographic rank (from 0) of a permutation of `0..n` among all the n! permutations.
+    /// Compact storage of orderings: n <= 34 always fits u128.
+    /// Panics when self is not a permutation, see `lehmer`.
+    fn permrank(self) -> u128 {
+        <&[usize]>::from_factoradic(&self.lehmer())
+    }
+
+    /// The permutation of `0..n` of the given lexicographic rank, inverse of `permrank`.
+    /// Panics when rank >= n!
+    fn permunrank(n: usize, rank: u128) -> Vec<usize> {
+        <&[usize]>::from_lehmer(&<&[usize]>::factoradic(rank, n))
+    }
+
+    /// The n digits of x in the factorial number system, most significant first:
+    /// `x = sum of digits[i]*(n-1-i)!`, where `digits[i] < n-i`. Panics when x >= n!
+    fn factoradic(x: u128, n: usize) -> Vec<usize> {
+        let mut digits = vec![0_usize; n];
+        let mut rest = x;
+        for (i, d) in digits.iter_mut().enumerate().rev() {
+            let base = (n - i) as u128;
+            *d = (rest % base) as usize;
+            rest /= base;
+        }
+        assert!(rest == 0, "factoradic: {x} does not fit {n} digits");
+        digits
+    }
+
+    /// The number given by its factorial number system digits, see `factoradic`.
+    /// Panics on invalid digits or u128 overflow.
+    fn from_factoradic(digits: &[usize]) -> u128 {
+        let n = digits.len();
+        digits.iter().enumerate().fold(0_u128, |x, (i, &d)| {
+            assert!(d < n - i, "from_factoradic: digit {d} at {i} out of range");
+            x.checked_mul((n - i) as u128)
+                .and_then(|x| x.checked_add(d as u128))
+                .expect("from_factoradic: u128 overflow")
+        })
+    }
 }
@@ -0,0 +1,143 @@
+/// What a `SortedWalk` yields from its two sorted sequences
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WalkMode {
+    /// All the items of both, like `merge`
+    Merge,
+    /// Equal items only once, like `unite_indexed`
+    Unite,
+    /// Items present in both, like `intersect`
+    Intersect,
+    /// Items of the first that are not cancelled out by the second, like `diff`
+    Diff,
+}
+
+/// Lazy walk over two ascending sorted slices (or slices sorted via their sort indices).
+/// Yields references to their items in ascending order, as selected by its `WalkMode`.
+/// Nothing is cloned or allocated, so the results can be chained, counted
+/// or short-circuited without materialising them.
+/// Constructed by `merge_iter`, `intersect_iter`, `diff_iter` and the `_indexed_iter` methods of `Vecops`.
+pub struct SortedWalk<'a, 'b, T> {
+    v1: &'a [T],
+    ix1: Option<&'b [usize]>,
+    i1: usize,
+    v2: &'a [T],
+    ix2: Option<&'b [usize]>,
+    i2: usize,
+    mode: WalkMode,
+}
+
+impl<'a, 'b, T> SortedWalk<'a, 'b, T> {
+    /// New walk over explicitly sorted `v1` and `v2`, or over data sorted by
+    /// their indices `ix1` and `ix2`, when given.
+    pub fn new(
+        v1: &'a [T],
+        ix1: Option<&'b [usize]>,
+        v2: &'a [T],
+        ix2: Option<&'b [usize]>,
+        mode: WalkMode,
+    ) -> Self {
+        SortedWalk {
+            v1,
+            ix1,
+            i1: 0,
+            v2,
+            ix2,
+            i2: 0,
+            mode,
+        }
+    }
+
+    /// The current head item of v1, if any
+    fn head1(&self) -> Option<&'a T> {
+        match self.ix1 {
+            Some(ix) => ix.get(self.i1).map(|&i| &self.v1[i]),
+            None => self.v1.get(self.i1),
+        }
+    }
+
+    /// The current head item of v2, if any
+    fn head2(&self) -> Option<&'a T> {
+        match self.ix2 {
+            Some(ix) => ix.get(self.i2).map(|&i| &self.v2[i]),
+            None => self.v2.get(self.i2),
+        }
+    }
+}
+
+impl<'a, T> Iterator for SortedWalk<'a, '_, T>
+where
+    T: PartialOrd,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        use WalkMode::*;
+        loop {
+            match (self.head1(), self.head2()) {
+                (None, None) => return None,
+                (Some(x), None) => {
+                    // v2 is now processed
+                    if self.mode == Intersect {
+                        return None;
+                    };
+                    self.i1 += 1;
+                    return Some(x);
+                }
+                (None, Some(y)) => {
+                    // v1 is now processed
+                    if self.mode == Intersect || self.mode == Diff {
+                        return None;
+                    };
+                    self.i2 += 1;
+                    return Some(y);
+                }
+                (Some(x), Some(y)) => {
+                    if x < y {
+                        self.i1 += 1;
+                        if self.mode == Intersect {
+                            continue;
+                        };
+                        return Some(x);
+                    };
+                    if x > y {
+                        self.i2 += 1;
+                        if self.mode == Intersect || self.mode == Diff {
+                            continue;
+                        };
+                        return Some(y);
+                    };
+                    // here they are equal
+                    match self.mode {
+                        Merge => {
+                            // consume the first, the second will follow
+                            self.i1 += 1;
+                            return Some(x);
+                        }
+                        Unite | Intersect => {
+                            // consume one, skip both
+                            self.i1 += 1;
+                            self.i2 += 1;
+                            return Some(x);
+                        }
+                        Diff => {
+                            // subtract them out
+                            self.i1 += 1;
+                            self.i2 += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let rest1 = self.ix1.map_or(self.v1.len(), |ix| ix.len()) - self.i1;
+        let rest2 = self.ix2.map_or(self.v2.len(), |ix| ix.len()) - self.i2;
+        match self.mode {
+            WalkMode::Merge => (rest1 + rest2, Some(rest1 + rest2)),
+            WalkMode::Unite => (rest1.max(rest2), Some(rest1 + rest2)),
+            WalkMode::Intersect => (0, Some(rest1.min(rest2))),
+            WalkMode::Diff => (0, Some(rest1)),
+        }
+    }
+}
@@ -3,12 +3,30 @@
 
 /// Implementation of trait Indices for `&[usize]`
 pub mod indices;
+/// Lazy iterators over sorted slices
+pub mod iters;
+/// Implementation of trait Matrix for `&[R]`, where `R: AsRef<[T]>` are rows
+pub mod matrix;
+/// Streaming MinMax accumulator, mergeable over chunks of data
+pub mod minmax;
+/// Multi-key (lexicographic) sorting, ranking and searching of table columns
+pub mod multikey;
 /// Implementation of trait Mutops for `&mut[T]`
 pub mod mutops;
 /// Utilities for serializing, writing and printing (optionally in colours) generic vectors.
 pub mod printing;
+/// Seeded random permutations, shuffles and sampling, with a small built-in generator
+pub mod random;
+/// Dynamic ranks and order statistics, backed by a Fenwick tree
+pub mod ranktree;
+/// Range minimum and maximum queries: sparse table and segment tree
+pub mod rmq;
 /// Implementation of trait Search for Range<T>
 pub mod search;
+/// Implementation of trait Setops for `&[T]`
+pub mod setops;
+/// String sorts (MSD radix) and collation comparators
+pub mod strings;
 /// Implementation of trait Vecops for `&[T]`
 pub mod vecops;
 
@@ -16,6 +34,7 @@ use core::{
     cmp::{Ordering, Ordering::*, Reverse},
     ops::Range
 };
+use iters::SortedWalk;
 use printing::*;
 use std::{collections::BinaryHeap, fs::File, io, io::Write};
 
@@ -66,6 +85,68 @@ where
     }
 }
 
+/// Measure of presortedness of data, as given by `Vecops::sortedness`.
+/// Helps to choose the sorting strategy.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct Sortedness {
+    /// Number of items
+    pub len: usize,
+    /// Number of maximal non-descending runs (1 when sorted, len when strictly descending)
+    pub runs: usize,
+    /// Number of strictly descending runs of at least two items
+    pub descruns: usize,
+    /// Estimated fraction of inverted pairs: 0 sorted, about 0.5 random, 1 strictly descending.
+    /// Exact for up to 128 items, otherwise estimated from an evenly spaced sample.
+    pub inversions: f64,
+}
+
+impl Sortedness {
+    /// True when the data is already in ascending (non-descending) order
+    pub fn is_sorted(&self) -> bool {
+        self.runs <= 1
+    }
+}
+
+/// Display implementation for Sortedness struct
+impl std::fmt::Display for Sortedness {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "len: {YL}{}{UN}, runs: {GR}{}{UN}, descending runs: {GR}{}{UN}, inversions: {GR}{:.4}{UN}",
+            self.len, self.runs, self.descruns, self.inversions
+        )
+    }
+}
+
+/// Sorting algorithm chosen by `Vecops::sort_choice` to suit the data
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortChoice {
+    /// Data already sorted, only the identity index is needed
+    Sorted,
+    /// Short data: insert log sort, `isort_indexed`
+    Insert,
+    /// Presorted data (few runs, or nearly sorted or reversed): `mergesort_adaptive`
+    Adaptive,
+    /// Many repeats or skewed distribution: `mergesort_indexed`
+    Merge,
+    /// Long, well spread data: `hashsort_indexed`
+    Hash,
+}
+
+/// Display implementation for SortChoice, suitable for logging
+impl std::fmt::Display for SortChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            SortChoice::Sorted => "already sorted",
+            SortChoice::Insert => "isort_indexed",
+            SortChoice::Adaptive => "mergesort_adaptive",
+            SortChoice::Merge => "mergesort_indexed",
+            SortChoice::Hash => "hashsort_indexed",
+        };
+        write!(f, "{GR}{name}{UN}")
+    }
+}
+
 /// function to sort f64s
 pub fn qsortf64(v: &mut [f64]) {
     v.sort_unstable_by(|a, b| a.total_cmp(b))
@@ -151,13 +232,30 @@ pub trait Indices {
     fn complindex(self) -> Vec<usize>;
     /// Using a subspace index, projects `v`, into it. 
     fn select<T:Clone>(self, v: &[T]) -> Vec<T>;
+    /// Using a subspace index, refs to the projection of `v`, zero-clone `select`
+    fn select_refs<T>(self, v: &[T]) -> Vec<&T>;
     /// Given a complete (sort) index, extracts indicated values from `v`
     fn unindex<T:Clone>(self, v: &[T], ascending: bool) -> Vec<T>;
+    /// Given a complete (sort) index, refs to indicated values of `v`, zero-clone `unindex`
+    fn unindex_refs<T>(self, v: &[T], ascending: bool) -> Vec<&T>;
     /// Correlation coefficient of two &[usize] slices.
     /// Pearsons on raw data, Spearman's when applied to ranks.
     fn ucorrelation(self, v: &[usize]) -> f64;
     /// Potentially useful clone-recast of &[usize] to Vec<f64>
     fn indx_to_f64(self) -> Vec<f64>;
+    /// Lehmer code of a permutation: the number of lesser items following each item.
+    /// Panics on repeated or out of range items
+    fn lehmer(self) -> Vec<usize>;
+    /// Indices::from_lehmer(code) decodes Lehmer code back into its permutation
+    fn from_lehmer(code: &[usize]) -> Vec<usize>;
+    /// Lexicographic rank of a permutation of 0..n (n <= 34)
+    fn permrank(self) -> u128;
+    /// Indices::permunrank(n,rank) is the permutation of 0..n with lexicographic rank `rank`
+    fn permunrank(n: usize, rank: u128) -> Vec<usize>;
+    /// Indices::factoradic(x,n) gives n digits of x in the factorial number system
+    fn factoradic(x: u128, n: usize) -> Vec<usize>;
+    /// Indices::from_factoradic(digits) is the number given by its factorial number system digits
+    fn from_factoradic(digits: &[usize]) -> u128;
 }
 
 /// Methods to manipulate generic Vecs and slices of type `&[T]`
@@ -177,6 +275,26 @@ pub trait Vecops<'a, T> {
     fn ref_vec(self, rng: Range<usize>) -> Vec<&'a T>;
     /// Helper function to copy and cast entire &[T] to `Vec<f64>`.
     fn tof64(self) -> Vec<f64>
+    where
+        T: Clone,
+        f64: From<T>;
+    /// Sample covariance of self and v, numerically stable
+    fn covariance(self, v: &[T]) -> f64
+    where
+        T: Clone,
+        f64: From<T>;
+    /// Pearson's correlation of self and v, numerically stable
+    fn correlation(self, v: &[T]) -> f64
+    where
+        T: Clone,
+        f64: From<T>;
+    /// Weighted Pearson's correlation of self and v, with weights w
+    fn wcorrelation(self, v: &[T], w: &[f64]) -> f64
+    where
+        T: Clone,
+        f64: From<T>;
+    /// Partial correlation of self and y, controlling for z
+    fn partial_correlation(self, y: &[T], z: &[T]) -> f64
     where
         T: Clone,
         f64: From<T>;
@@ -204,6 +322,31 @@ pub trait Vecops<'a, T> {
     fn minmax_indexed(self, idx: &[usize], i: usize, n: usize) -> MinMax<T>
     where
         T: PartialOrd + Clone;
+    /// MinMax of each sliding window of length w, by monotonic deques
+    fn sliding_minmax(self, w: usize) -> Vec<MinMax<T>>
+    where
+        T: PartialOrd + Clone;
+    /// Median of each sliding window of length w
+    fn rolling_median(self, w: usize, quantify: impl Fn(&T) -> f64) -> Vec<f64>
+    where
+        T: PartialOrd + Clone;
+    /// Rank of the newest item within each sliding window of length w
+    fn rolling_rank(self, w: usize) -> Vec<usize>
+    where
+        T: PartialOrd + Clone;
+    /// Longest strictly increasing subsequence (by comparator c), as subscripts.
+    /// Reverse `c` for the longest decreasing subsequence.
+    fn lis_indexed<F>(self, c: F) -> Vec<usize>
+    where
+        F: Fn(&T, &T) -> Ordering;
+    /// Longest non-decreasing subsequence (by comparator c), as subscripts
+    fn lnds_indexed<F>(self, c: F) -> Vec<usize>
+    where
+        F: Fn(&T, &T) -> Ordering;
+    /// Patience sort: the minimal cover of self by non-decreasing subsequences (piles of subscripts)
+    fn patience_piles<F>(self, c: F) -> Vec<Vec<usize>>
+    where
+        F: Fn(&T, &T) -> Ordering;
     /// Reversed copy of self
     fn revs(self) -> Vec<T>
     where
@@ -220,6 +363,18 @@ pub trait Vecops<'a, T> {
     fn occurs(self, val: T) -> usize
     where
         T: PartialOrd;
+    /// Run-length encoding: `(value, start, length)` of each run of equal items
+    fn group_runs(self) -> Vec<(T, usize, usize)>
+    where
+        T: PartialEq + Clone;
+    /// All positions of each distinct value, grouped via sort index `idx`
+    fn group_by_index(self, idx: &[usize]) -> Vec<(T, Vec<usize>)>
+    where
+        T: PartialOrd + Clone;
+    /// Histogram of distinct values and their counts, ordered by value or by frequency
+    fn value_counts(self, byfreq: bool) -> Vec<(T, usize)>
+    where
+        T: PartialOrd + Clone;
     /// Unites (concatenates) two unsorted slices. For union of sorted slices, use `merge`
     fn unite_unsorted(self, v: &[T]) -> Vec<T>
     where
@@ -252,10 +407,90 @@ pub trait Vecops<'a, T> {
     fn partition_indexed(self, pivot: &T) -> (Vec<usize>, Vec<usize>, Vec<usize>)
     where
         T: PartialOrd + Clone;
+    /// Lazy `merge` of two sorted slices, yielding references
+    fn merge_iter(self, v2: &'a [T]) -> SortedWalk<'a, 'a, T>
+    where
+        T: PartialOrd;
+    /// Lazy `intersect` of two sorted slices, yielding references
+    fn intersect_iter(self, v2: &'a [T]) -> SortedWalk<'a, 'a, T>
+    where
+        T: PartialOrd;
+    /// Lazy `diff` of two sorted slices, yielding references
+    fn diff_iter(self, v2: &'a [T]) -> SortedWalk<'a, 'a, T>
+    where
+        T: PartialOrd;
+    /// Lazy `unite_indexed` of two index sorted slices, yielding references
+    fn unite_indexed_iter<'b>(self, ix1: &'b [usize], v2: &'a [T], ix2: &'b [usize]) -> SortedWalk<'a, 'b, T>
+    where
+        T: PartialOrd;
+    /// Lazy `intersect_indexed` of two index sorted slices, yielding references
+    fn intersect_indexed_iter<'b>(self, ix1: &'b [usize], v2: &'a [T], ix2: &'b [usize]) -> SortedWalk<'a, 'b, T>
+    where
+        T: PartialOrd;
+    /// Lazy `diff_indexed` of two index sorted slices, yielding references
+    fn diff_indexed_iter<'b>(self, ix1: &'b [usize], v2: &'a [T], ix2: &'b [usize]) -> SortedWalk<'a, 'b, T>
+    where
+        T: PartialOrd;
+    /// Lazy `partition`: references to the items that are `which` (Less, Equal or Greater) than the pivot
+    fn partition_iter<'b>(self, pivot: &'b T, which: Ordering) -> impl Iterator<Item = &'a T> + 'b
+    where
+        'a: 'b,
+        T: PartialOrd + 'a;
+    /// Lazy `partition_indexed`: subscripts of the items that are `which` than the pivot
+    fn partition_indexed_iter<'b>(self, pivot: &'b T, which: Ordering) -> impl Iterator<Item = usize> + 'b
+    where
+        'a: 'b,
+        T: PartialOrd;
+    /// Reversed refs to self, zero-clone `revs`
+    fn revs_refs(self) -> Vec<&'a T>;
+    /// Refs to self with repeated items removed, zero-clone `sansrepeat`
+    fn sansrepeat_refs(self) -> Vec<&'a T>
+    where
+        T: PartialEq;
+    /// Refs to items smaller than pivot, equal, and greater, zero-clone `partition`
+    fn partition_refs(self, pivot: &T) -> (Vec<&'a T>, Vec<&'a T>, Vec<&'a T>)
+    where
+        T: PartialOrd;
+    /// Refs to merged sorted self and v2, zero-clone `merge`
+    fn merge_refs(self, v2: &'a [T]) -> Vec<&'a T>
+    where
+        T: PartialOrd;
+    /// Refs to intersected sorted self and v2, zero-clone `intersect`
+    fn intersect_refs(self, v2: &'a [T]) -> Vec<&'a T>
+    where
+        T: PartialOrd;
+    /// Refs to items of sorted self not in sorted v2, zero-clone `diff`
+    fn diff_refs(self, v2: &'a [T]) -> Vec<&'a T>
+    where
+        T: PartialOrd;
+    /// Refs to united index sorted self and v2, zero-clone `unite_indexed`
+    fn unite_indexed_refs(self, ix1: &[usize], v2: &'a [T], ix2: &[usize]) -> Vec<&'a T>
+    where
+        T: PartialOrd;
+    /// Refs to intersected index sorted self and v2, zero-clone `intersect_indexed`
+    fn intersect_indexed_refs(self, ix1: &[usize], v2: &'a [T], ix2: &[usize]) -> Vec<&'a T>
+    where
+        T: PartialOrd;
+    /// Refs to items of index sorted self not in v2, zero-clone `diff_indexed`
+    fn diff_indexed_refs(self, ix1: &[usize], v2: &'a [T], ix2: &[usize]) -> Vec<&'a T>
+    where
+        T: PartialOrd;
+    /// Refs to sorted self (ascending or descending), zero-clone `sortm`
+    fn sortm_refs(self, ascending: bool) -> Vec<&'a T>
+    where
+        T: PartialOrd;
     /// Binary Search. Automatic descending PartialOrder detection.
     fn binsearch(self, target: &T) -> Range<usize>
     where
         T: PartialOrd + Copy;
+    /// Binary Search of data sorted by comparator `c`, returns the range of all matching items
+    fn binsearch_by<F>(self, target: &T, c: F) -> Range<usize>
+    where
+        F: Fn(&T, &T) -> Ordering;
+    /// Binary Search via index of data sorted by comparator `c`
+    fn binsearch_indexed_by<F>(self, idx: &[usize], target: &T, c: F) -> Range<usize>
+    where
+        F: Fn(&T, &T) -> Ordering;
     /// Binary Search via index. Automatic descending PartialOrder detection
     fn binsearch_indexed(self, idx: &[usize], target: &T) -> Range<usize>
     where
@@ -274,6 +509,45 @@ pub trait Vecops<'a, T> {
         T: PartialOrd + Clone;
     /// Stable Merge sort main method, giving sort index
     fn mergesort_indexed(self) -> Vec<usize>
+    where
+        T: PartialOrd + Clone;
+    /// Stable merge sort index by the keys (e.g. fields or tuples of fields) extracted by `key`
+    fn mergesort_indexed_by_key<K, F>(self, key: F) -> Vec<usize>
+    where
+        K: PartialOrd + Clone,
+        F: Fn(&T) -> K;
+    /// Hash sort index by the f64 keys projected by `key`
+    fn hashsort_indexed_by_key<F>(self, key: F) -> Vec<usize>
+    where
+        F: Fn(&T) -> f64;
+    /// Ranks by the keys extracted by `key`
+    fn rank_by_key<K, F>(self, key: F, ascending: bool) -> Vec<usize>
+    where
+        K: PartialOrd + Clone,
+        F: Fn(&T) -> K;
+    /// Binary Search for a key, via index sorted by the keys extracted by `key`
+    fn binsearch_indexed_by_key<K, F>(self, idx: &[usize], target: &K, key: F) -> Range<usize>
+    where
+        K: PartialOrd,
+        F: Fn(&T) -> K;
+    /// Adaptive (natural) stable merge sort giving sort index, fast on presorted data
+    fn mergesort_adaptive(self) -> Vec<usize>
+    where
+        T: PartialOrd + Clone;
+    /// Runs and estimated inversions of the data, see `Sortedness`
+    fn sortedness(self) -> Sortedness
+    where
+        T: PartialOrd;
+    /// Sorting algorithm best suited to the data, see `SortChoice`
+    fn sort_choice(self, quantify: impl Copy + Fn(&T) -> f64) -> SortChoice
+    where
+        T: PartialOrd + Clone;
+    /// Sort index by the best suited algorithm, also returning the choice made
+    fn smart_sort_indexed(self, quantify: impl Copy + Fn(&T) -> f64) -> (Vec<usize>, SortChoice)
+    where
+        T: PartialOrd + Clone;
+    /// Sorted data by the best suited algorithm, also returning the choice made
+    fn smart_sort(self, quantify: impl Copy + Fn(&T) -> f64, ascending: bool) -> (Vec<T>, SortChoice)
     where
         T: PartialOrd + Clone;
     /// Utility used by mergesort_indexed
@@ -288,6 +562,16 @@ pub trait Vecops<'a, T> {
     fn rank(self, ascending: bool) -> Vec<usize>
     where
         T: PartialOrd + Clone;
+    /// Updates sort index `idx` (and optionally its `ranks`) after new items were pushed onto self
+    fn append_indexed(self, idx: &mut Vec<usize>, ranks: Option<&mut Vec<usize>>)
+    where
+        T: PartialOrd + Clone;
+    /// Updates sort index `idx` (and optionally its `ranks`) after the value of `self[pos]` changed
+    fn update_indexed(self, idx: &mut [usize], pos: usize, ranks: Option<&mut Vec<usize>>)
+    where
+        T: PartialOrd;
+    /// Updates sort index `idx` (and optionally its `ranks`) after item `pos` was removed from self
+    fn remove_indexed(self, idx: &mut Vec<usize>, pos: usize, ranks: Option<&mut Vec<usize>>);
     /// Utility, swaps any two items into ascending order
     fn isorttwo(self, idx: &mut [usize], i0: usize, i1: usize)
     where
@@ -300,6 +584,10 @@ pub trait Vecops<'a, T> {
     fn hashsort_indexed(self, quantify: impl Copy + Fn(&T) -> f64) -> Vec<usize>
     where
         T: PartialOrd + Clone;
+    /// Stable hash sort by sampled quantiles, robust to skewed, clustered and infinite data
+    fn hashsort_robust_indexed(self, quantify: impl Copy + Fn(&T) -> f64) -> Vec<usize>
+    where
+        T: PartialOrd;
     /// Utility used by hashsort_indexed
     fn hashsortslice(
         self,
@@ -353,6 +641,212 @@ pub trait Vecops<'a, T> {
         F: Fn(&T, &T) -> Ordering;
 }
 
+/// Set and multiset operations on explicitly sorted (ascending) slices `&[T]`,
+/// or on slices sorted via their sort indices (the `_indexed` methods).
+/// When `multi` is false, repeated items are treated as one (plain sets),
+/// when true, their multiplicities count (multisets).
+pub trait Setops<'a, T> {
+    /// General set operation: every distinct item is output `f(c1,c2)` times,
+    /// given its multiplicities c1 in self and c2 in v2
+    fn setop(self, v2: &[T], f: impl Fn(usize, usize) -> usize) -> Vec<T>
+    where
+        T: PartialOrd + Clone;
+    /// Union, multisets: maximum of multiplicities (use `merge` for their sum)
+    fn union(self, v2: &[T], multi: bool) -> Vec<T>
+    where
+        T: PartialOrd + Clone;
+    /// Intersection, multisets: minimum of multiplicities
+    fn intersection(self, v2: &[T], multi: bool) -> Vec<T>
+    where
+        T: PartialOrd + Clone;
+    /// Difference self - v2, multisets: multiplicities subtracted
+    fn difference(self, v2: &[T], multi: bool) -> Vec<T>
+    where
+        T: PartialOrd + Clone;
+    /// Symmetric difference, multisets: absolute difference of multiplicities
+    fn symdiff(self, v2: &[T], multi: bool) -> Vec<T>
+    where
+        T: PartialOrd + Clone;
+    /// Is self a subset of v2
+    #[allow(clippy::wrong_self_convention)]
+    fn is_subset(self, v2: &[T], multi: bool) -> bool
+    where
+        T: PartialOrd;
+    /// Do self and v2 have no items in common
+    #[allow(clippy::wrong_self_convention)]
+    fn is_disjoint(self, v2: &[T]) -> bool
+    where
+        T: PartialOrd;
+    /// Jaccard similarity: size of intersection / size of union
+    fn jaccard(self, v2: &[T], multi: bool) -> f64
+    where
+        T: PartialOrd;
+    /// Zero-clone `setop`, returns refs to the items of self and v2
+    fn setop_refs(self, v2: &'a [T], f: impl Fn(usize, usize) -> usize) -> Vec<&'a T>
+    where
+        T: PartialOrd;
+    /// Zero-clone `union`
+    fn union_refs(self, v2: &'a [T], multi: bool) -> Vec<&'a T>
+    where
+        T: PartialOrd;
+    /// Zero-clone `intersection`
+    fn intersection_refs(self, v2: &'a [T], multi: bool) -> Vec<&'a T>
+    where
+        T: PartialOrd;
+    /// Zero-clone `difference`
+    fn difference_refs(self, v2: &'a [T], multi: bool) -> Vec<&'a T>
+    where
+        T: PartialOrd;
+    /// Zero-clone `symdiff`
+    fn symdiff_refs(self, v2: &'a [T], multi: bool) -> Vec<&'a T>
+    where
+        T: PartialOrd;
+    /// Zero-clone `setop_indexed`
+    fn setop_indexed_refs(
+        self,
+        ix1: &[usize],
+        v2: &'a [T],
+        ix2: &[usize],
+        f: impl Fn(usize, usize) -> usize,
+    ) -> Vec<&'a T>
+    where
+        T: PartialOrd;
+    /// General set operation on index sorted self and v2, see `setop`
+    fn setop_indexed(
+        self,
+        ix1: &[usize],
+        v2: &[T],
+        ix2: &[usize],
+        f: impl Fn(usize, usize) -> usize,
+    ) -> Vec<T>
+    where
+        T: PartialOrd + Clone;
+    /// Union of index sorted self and v2
+    fn union_indexed(self, ix1: &[usize], v2: &[T], ix2: &[usize], multi: bool) -> Vec<T>
+    where
+        T: PartialOrd + Clone;
+    /// Intersection of index sorted self and v2
+    fn intersection_indexed(self, ix1: &[usize], v2: &[T], ix2: &[usize], multi: bool) -> Vec<T>
+    where
+        T: PartialOrd + Clone;
+    /// Difference of index sorted self and v2
+    fn difference_indexed(self, ix1: &[usize], v2: &[T], ix2: &[usize], multi: bool) -> Vec<T>
+    where
+        T: PartialOrd + Clone;
+    /// Symmetric difference of index sorted self and v2
+    fn symdiff_indexed(self, ix1: &[usize], v2: &[T], ix2: &[usize], multi: bool) -> Vec<T>
+    where
+        T: PartialOrd + Clone;
+    /// Is index sorted self a subset of index sorted v2
+    #[allow(clippy::wrong_self_convention)]
+    fn is_subset_indexed(self, ix1: &[usize], v2: &[T], ix2: &[usize], multi: bool) -> bool
+    where
+        T: PartialOrd;
+    /// Do index sorted self and v2 have no items in common
+    #[allow(clippy::wrong_self_convention)]
+    fn is_disjoint_indexed(self, ix1: &[usize], v2: &[T], ix2: &[usize]) -> bool
+    where
+        T: PartialOrd;
+    /// Jaccard similarity of index sorted self and v2
+    fn jaccard_indexed(self, ix1: &[usize], v2: &[T], ix2: &[usize], multi: bool) -> f64
+    where
+        T: PartialOrd;
+    /// Sort-merge join of sorted key slices self and v2, giving the pairs of joined row positions
+    fn join(self, v2: &[T], kind: Join) -> Vec<(Option<usize>, Option<usize>)>
+    where
+        T: PartialOrd;
+    /// Sort-merge join of index sorted key slices self and v2, giving the pairs of joined row positions
+    fn join_indexed(
+        self,
+        ix1: &[usize],
+        v2: &[T],
+        ix2: &[usize],
+        kind: Join,
+    ) -> Vec<(Option<usize>, Option<usize>)>
+    where
+        T: PartialOrd;
+    /// Intersection of many sorted lists, with the positions of each common item in every list
+    fn intersect_many(lists: &[&'a [T]]) -> Vec<(&'a T, Vec<usize>)>
+    where
+        T: PartialOrd;
+    /// Union of many sorted lists, with all the (list, position) occurrences of each item
+    fn unite_many(lists: &[&'a [T]]) -> Vec<(&'a T, Vec<(usize, usize)>)>
+    where
+        T: PartialOrd;
+    /// Items present in at least m of the sorted lists, with all their (list, position) occurrences
+    fn threshold_many(lists: &[&'a [T]], m: usize) -> Vec<(&'a T, Vec<(usize, usize)>)>
+    where
+        T: PartialOrd;
+}
+
+/// Axis of 2D data, along which `Matrix` methods operate
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Axis {
+    /// Along each row, over its columns
+    Row,
+    /// Along each column, over the rows
+    Col,
+}
+
+/// Kind of correlation coefficient, computed by `Matrix::correlations`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Correlation {
+    /// Pearson's linear correlation of the values
+    Pearson,
+    /// Spearman's rank correlation, with tied items given their average ranks
+    Spearman,
+    /// Kendall's tau-b, corrected for ties
+    Kendall,
+}
+
+/// Kind of join of two tables on their keys, performed by `Setops::join`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Join {
+    /// Pairs of rows with equal keys
+    Inner,
+    /// Inner, plus the unmatched rows of the left table, paired with None
+    Left,
+    /// Inner, plus the unmatched rows of the right table, paired with None
+    Right,
+    /// Inner, plus the unmatched rows of both tables
+    Full,
+    /// Rows of the left table that have some match, each once
+    Semi,
+    /// Rows of the left table that have no match
+    Anti,
+}
+
+/// Indexing operations on 2D data, given as a slice of (possibly ragged) rows,
+/// such as `&[Vec<T>]` or `&[&[T]]`
+pub trait Matrix<T> {
+    /// Number of rows
+    fn nrows(self) -> usize;
+    /// Number of columns: the length of the longest row
+    fn ncols(self) -> usize;
+    /// Stable sort index of rows in lexicographic order
+    fn sortrows_indexed(self) -> Vec<usize>
+    where
+        T: PartialOrd;
+    /// Sort indices along each row (Axis::Row) or each column (Axis::Col)
+    fn argsort(self, axis: Axis) -> Vec<Vec<usize>>
+    where
+        T: PartialOrd + Clone;
+    /// Ranks along each row (Axis::Row) or each column (Axis::Col)
+    fn ranks(self, axis: Axis, ascending: bool) -> Vec<Vec<usize>>
+    where
+        T: PartialOrd + Clone;
+    /// MinMax of each row, None for empty rows
+    fn rowsminmax(self) -> Vec<Option<MinMax<T>>>
+    where
+        T: PartialOrd + Clone;
+    /// Subscripts of the distinct rows (their first occurrences), in data order
+    fn dedup_rows(self) -> Vec<usize>
+    where
+        T: PartialOrd;
+    /// Symmetric matrix of correlations between all the rows (variables)
+    fn correlations(self, kind: Correlation, quantify: impl Copy + Fn(&T) -> f64) -> Vec<Vec<f64>>;
+}
+
 /// Mutable Operators on `&mut[T]`
 pub trait Mutops<T> {
     /// Associated method `part` partitions `s: &mut [&T]` within range `rng`, using comparator `c`.  
@@ -406,6 +900,11 @@ pub trait Mutops<T> {
     fn muthashsort(self, quantify: impl Copy + Fn(&T) -> f64)
     where
         T: PartialOrd + Clone;
+    /// Stable hash sort, robust to skewed, clustered and infinite data.
+    /// Needs O(n) auxiliary memory for its sort index
+    fn muthashsort_stable(self, quantify: impl Copy + Fn(&T) -> f64)
+    where
+        T: PartialOrd;
     /// Sorts n items from i in self. Used by muthashsort.
     fn muthashsortslice(
         self,
@@ -419,6 +918,56 @@ pub trait Mutops<T> {
     /// Mutable insert logsort. Pass in reversed comparator `c` for descending sort
     fn mutisort<F>(self, rng: Range<usize>, c: F)
     where
-        T: Copy,
         F: Fn(&T, &T) -> Ordering;
+    /// In-place introsort by comparator `c`, O(n log n) worst case
+    fn mutsort_by<F>(self, c: F)
+    where
+        F: Fn(&T, &T) -> Ordering;
+    /// Rearranges self into the lexicographically next permutation, false when wrapped around
+    fn next_permutation(self) -> bool
+    where
+        T: PartialOrd;
+    /// Rearranges self into the lexicographically previous permutation, false when wrapped around
+    fn prev_permutation(self) -> bool
+    where
+        T: PartialOrd;
+    /// Three-way in-place partition of `rng` by the `pivot` strategy, returns (lt_end, gt_start)
+    fn mutpartition(
+        self,
+        rng: &Range<usize>,
+        pivot: &Pivot<T>,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> (usize, usize);
+    /// Stable three-way partition of `rng` by the `pivot` strategy, returns (lt_end, gt_start)
+    /// Not in place: clones the items of `rng` into O(n) temporary buffers
+    fn mutpartition_stable(
+        self,
+        rng: &Range<usize>,
+        pivot: &Pivot<T>,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> (usize, usize)
+    where
+        T: Clone;
+    /// Stable partition of `rng` into buckets delimited by sorted `splitters`, returns bucket boundaries
+    /// Not in place: clones the items of `rng` into O(n) temporary buffers, one per bucket
+    fn mutbuckets(
+        self,
+        rng: &Range<usize>,
+        splitters: &[T],
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Vec<usize>
+    where
+        T: Clone;
+}
+
+/// Pivot selection strategies for the in-place partitioning of `Mutops`
+pub enum Pivot<T> {
+    /// Median of the first, the middle and the last items
+    MedianOf3,
+    /// Tukey's ninther: median of three medians of three, for longer ranges
+    Ninther,
+    /// Randomly chosen item, reproducible from the given seed
+    Random(u64),
+    /// Pivot value supplied by the caller
+    Value(T),
 }
@@ -0,0 +1,300 @@
+use crate::{Axis, Correlation, Matrix, MinMax, Vecops};
+use core::cmp::Ordering::*;
+
+impl<T, R> Matrix<T> for &[R]
+where
+    R: AsRef<[T]>,
+{
+    /// Number of rows
+    fn nrows(self) -> usize {
+        self.len()
+    }
+
+    /// Number of columns: the length of the longest row.
+    /// Shorter (ragged) rows simply lack their trailing columns.
+    fn ncols(self) -> usize {
+        self.iter().map(|row| row.as_ref().len()).max().unwrap_or(0)
+    }
+
+    /// Stable sort index of rows in lexicographic order:
+    /// rows are compared item by item, the first unequal pair decides.
+    /// A row that is a prefix of a longer row goes first.
+    fn sortrows_indexed(self) -> Vec<usize>
+    where
+        T: PartialOrd,
+    {
+        let mut idx = Vec::from_iter(0..self.len());
+        idx.sort_by(|&i, &j| {
+            self[i]
+                .as_ref()
+                .partial_cmp(self[j].as_ref())
+                .unwrap_or(Equal)
+        });
+        idx
+    }
+
+    /// Sort indices (by the stable `mergesort_indexed`) along each row or each column.
+    /// `Axis::Row`: one sort index of column subscripts for each row.
+    /// `Axis::Col`: for each column, the subscripts of rows in the sort order of that column.
+    /// Ragged rows that are too short to have the column are left out of it.
+    fn argsort(self, axis: Axis) -> Vec<Vec<usize>>
+    where
+        T: PartialOrd + Clone,
+    {
+        match axis {
+            Axis::Row => self.iter().map(|row| sortindex(row.as_ref())).collect(),
+            Axis::Col => (0..self.ncols())
+                .map(|j| {
+                    let (rows, vals) = column(self, j);
+                    sortindex(&vals).iter().map(|&k| rows[k]).collect()
+                })
+                .collect(),
+        }
+    }
+
+    /// Ranks along each row or each column, e.g. for Spearman correlations.
+    /// `Axis::Row`: the ranks of the items of each row.
+    /// `Axis::Col`: for each column, the ranks of its items in row order.
+    /// Ragged rows that are too short to have the column are left out of its ranking.
+    /// Tied items are ranked in their order of appearance.
+    fn ranks(self, axis: Axis, ascending: bool) -> Vec<Vec<usize>>
+    where
+        T: PartialOrd + Clone,
+    {
+        let rank = |v: &[T]| {
+            if v.is_empty() {
+                Vec::new()
+            } else {
+                v.rank(ascending)
+            }
+        };
+        match axis {
+            Axis::Row => self.iter().map(|row| rank(row.as_ref())).collect(),
+            Axis::Col => (0..self.ncols()).map(|j| rank(&column(self, j).1)).collect(),
+        }
+    }
+
+    /// MinMax of each row, None for empty rows
+    fn rowsminmax(self) -> Vec<Option<MinMax<T>>>
+    where
+        T: PartialOrd + Clone,
+    {
+        self.iter()
+            .map(|row| {
+                let row = row.as_ref();
+                if row.is_empty() {
+                    None
+                } else {
+                    Some(row.minmax())
+                }
+            })
+            .collect()
+    }
+
+    /// Subscripts of the distinct rows, in data order.
+    /// Of identical rows, only the first occurrence is kept.
+    /// Found via the stable `sortrows_indexed`, so that identical rows are adjacent.
+    fn dedup_rows(self) -> Vec<usize>
+    where
+        T: PartialOrd,
+    {
+        let idx = self.sortrows_indexed();
+        let mut kept: Vec<usize> = idx
+            .iter()
+            .enumerate()
+            .filter(|&(pos, &i)| pos == 0 || self[idx[pos - 1]].as_ref() != self[i].as_ref())
+            .map(|(_, &i)| i)
+            .collect();
+        kept.sort_unstable();
+        kept
+    }
+
+    /// Symmetric matrix of correlation coefficients between all pairs of the rows,
+    /// each row holding one variable (all of the same length).
+    /// The items are mapped to f64 by `quantify`, which for the rank correlations
+    /// (Spearman and Kendall) only needs to preserve their order.
+    /// Each variable is prepared (standardised, ranked or sorted) only once,
+    /// Spearman's ranks give tied items their average rank.
+    /// Kendall's tau-b uses Knight's O(n log n) algorithm, counting the discordant pairs by merge sort.
+    /// Zero variance variables, and variables of fewer than two observations, give NaN coefficients.
+    /// The result is printable as 2D data by `Printing`.
+    fn correlations(self, kind: Correlation, quantify: impl Copy + Fn(&T) -> f64) -> Vec<Vec<f64>> {
+        let vars: Vec<Vec<f64>> = self
+            .iter()
+            .map(|row| row.as_ref().iter().map(quantify).collect())
+            .collect();
+        let n = vars.first().map_or(0, |v| v.len());
+        assert!(
+            vars.iter().all(|v| v.len() == n),
+            "correlations: variables differ in length"
+        );
+        let k = vars.len();
+        if n < 2 {
+            // no variance without at least two observations
+            return vec![vec![f64::NAN; k]; k];
+        };
+        let mut res = vec![vec![0_f64; k]; k];
+        match kind {
+            Correlation::Pearson | Correlation::Spearman => {
+                let zs: Vec<Vec<f64>> = vars
+                    .iter()
+                    .map(|v| {
+                        if kind == Correlation::Spearman {
+                            standardise(&avranks(v))
+                        } else {
+                            standardise(v)
+                        }
+                    })
+                    .collect();
+                for i in 0..k {
+                    for j in i..k {
+                        let r: f64 = zs[i].iter().zip(&zs[j]).map(|(a, b)| a * b).sum();
+                        res[i][j] = r;
+                        res[j][i] = r;
+                    }
+                }
+            }
+            Correlation::Kendall => {
+                let idxs: Vec<Vec<usize>> = vars.iter().map(|v| sortindexf64(v)).collect();
+                for i in 0..k {
+                    for j in i..k {
+                        let tau = tau_b(&vars[i], &idxs[i], &vars[j]);
+                        res[i][j] = tau;
+                        res[j][i] = tau;
+                    }
+                }
+            }
+        }
+        res
+    }
+}
+
+/// Stable sort index of possibly empty `v`
+fn sortindex<T>(v: &[T]) -> Vec<usize>
+where
+    T: PartialOrd + Clone,
+{
+    if v.is_empty() {
+        Vec::new()
+    } else {
+        v.mergesort_indexed()
+    }
+}
+
+/// Column `j` of `m`: the subscripts of the rows that have it, and their items in it
+fn column<T, R>(m: &[R], j: usize) -> (Vec<usize>, Vec<T>)
+where
+    T: Clone,
+    R: AsRef<[T]>,
+{
+    m.iter()
+        .enumerate()
+        .filter_map(|(i, row)| row.as_ref().get(j).map(|x| (i, x.clone())))
+        .unzip()
+}
+
+/// Stable ascending sort index of f64s
+fn sortindexf64(v: &[f64]) -> Vec<usize> {
+    let mut idx = Vec::from_iter(0..v.len());
+    idx.sort_by(|&a, &b| v[a].total_cmp(&v[b]));
+    idx
+}
+
+/// Ranks from 1 to n, with tied items given the average of their ranks
+fn avranks(v: &[f64]) -> Vec<f64> {
+    let idx = sortindexf64(v);
+    let mut ranks = vec![0_f64; v.len()];
+    let mut start = 0;
+    while start < idx.len() {
+        let mut end = start + 1;
+        while end < idx.len() && v[idx[end]] == v[idx[start]] {
+            end += 1;
+        }
+        let average = (start + end + 1) as f64 / 2.0;
+        idx[start..end].iter().for_each(|&i| ranks[i] = average);
+        start = end;
+    }
+    ranks
+}
+
+/// Centered values scaled to unit length, so that their dot products are Pearson's correlations
+fn standardise(v: &[f64]) -> Vec<f64> {
+    let mean = v.iter().sum::<f64>() / v.len() as f64;
+    let centered: Vec<f64> = v.iter().map(|x| x - mean).collect();
+    let norm = centered.iter().map(|x| x * x).sum::<f64>().sqrt();
+    centered.iter().map(|x| x / norm).collect()
+}
+
+/// Number of pairs among tied items in runs of equal values in sorted `v`
+fn tiedpairs(v: &[f64]) -> usize {
+    let mut pairs = 0;
+    let mut start = 0;
+    while start < v.len() {
+        let mut end = start + 1;
+        while end < v.len() && v[end] == v[start] {
+            end += 1;
+        }
+        pairs += (end - start) * (end - start - 1) / 2;
+        start = end;
+    }
+    pairs
+}
+
+/// Kendall's tau-b of x and y by Knight's algorithm, given the stable sort index `xidx` of x
+fn tau_b(x: &[f64], xidx: &[usize], y: &[f64]) -> f64 {
+    let n = x.len();
+    if n < 2 {
+        return f64::NAN;
+    };
+    let mut ord = xidx.to_vec();
+    let (mut xties, mut jointties) = (0, 0);
+    let mut start = 0;
+    // order by x, then by y within the ties of x
+    while start < n {
+        let mut end = start + 1;
+        while end < n && x[ord[end]] == x[ord[start]] {
+            end += 1;
+        }
+        ord[start..end].sort_by(|&a, &b| y[a].total_cmp(&y[b]));
+        let ys: Vec<f64> = ord[start..end].iter().map(|&i| y[i]).collect();
+        xties += (end - start) * (end - start - 1) / 2;
+        jointties += tiedpairs(&ys);
+        start = end;
+    }
+    let mut ys: Vec<f64> = ord.iter().map(|&i| y[i]).collect();
+    let mut buf = vec![0_f64; n];
+    let discordant = inversions(&mut ys, &mut buf);
+    let yties = tiedpairs(&ys);
+    let pairs = (n * (n - 1) / 2) as f64;
+    // in f64, as the products of the pair counts overflow usize for long data
+    let numerator = pairs + jointties as f64 - (xties + yties) as f64 - 2.0 * discordant as f64;
+    numerator / ((pairs - xties as f64) * (pairs - yties as f64)).sqrt()
+}
+
+/// Merge sorts `v`, counting the strictly inverted pairs
+fn inversions(v: &mut [f64], buf: &mut [f64]) -> usize {
+    let n = v.len();
+    if n < 2 {
+        return 0;
+    };
+    let mid = n / 2;
+    let mut count = inversions(&mut v[..mid], &mut buf[..mid]) + inversions(&mut v[mid..], &mut buf[mid..]);
+    let (mut i, mut j, mut k) = (0, mid, 0);
+    while i < mid && j < n {
+        if v[j] < v[i] {
+            // v[j] is inverted with all the remaining items of the first half
+            count += mid - i;
+            buf[k] = v[j];
+            j += 1;
+        } else {
+            buf[k] = v[i];
+            i += 1;
+        };
+        k += 1;
+    }
+    buf[k..k + mid - i].copy_from_slice(&v[i..mid]);
+    k += mid - i;
+    buf[k..k + n - j].copy_from_slice(&v[j..n]);
+    v.copy_from_slice(&buf[..n]);
+    count
+}
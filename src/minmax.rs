@@ -0,0 +1,219 @@
+use crate::{printing::*, MinMax};
+
+/// Streaming accumulator of minimum and maximum values and their positions.
+/// Items are `push`ed (or `extend`ed) one at a time, without buffering.
+/// The position of each item is its count in the stream, starting from 0.
+/// Partial results, e.g. of chunks or threads, are combined by `merge`.
+/// Optionally also accumulates the sum, given a quantify function (see `with_sum`).
+#[derive(Clone, Debug)]
+pub struct MinMaxStats<T> {
+    /// Number of items seen
+    pub count: usize,
+    /// Minimum value, None when no items were seen
+    pub min: Option<T>,
+    /// Position of the first occurrence of the minimum
+    pub minfirst: usize,
+    /// Position of the last occurrence of the minimum
+    pub minlast: usize,
+    /// Maximum value, None when no items were seen
+    pub max: Option<T>,
+    /// Position of the first occurrence of the maximum
+    pub maxfirst: usize,
+    /// Position of the last occurrence of the maximum
+    pub maxlast: usize,
+    /// Sum of the quantified items, when quantify was given
+    pub sum: Option<f64>,
+    quantify: Option<fn(&T) -> f64>,
+}
+
+impl<T> Default for MinMaxStats<T> {
+    fn default() -> Self {
+        MinMaxStats {
+            count: 0,
+            min: None,
+            minfirst: 0,
+            minlast: 0,
+            max: None,
+            maxfirst: 0,
+            maxlast: 0,
+            sum: None,
+            quantify: None,
+        }
+    }
+}
+
+impl<T> MinMaxStats<T>
+where
+    T: PartialOrd + Clone,
+{
+    /// New empty accumulator
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// New empty accumulator, that also sums the items mapped to f64 by `quantify`
+    pub fn with_sum(quantify: fn(&T) -> f64) -> Self {
+        MinMaxStats {
+            sum: Some(0_f64),
+            quantify: Some(quantify),
+            ..Self::default()
+        }
+    }
+
+    /// Accumulates one more item.
+    /// Items that do not compare with the current extremes (e.g. NaN) do not replace them.
+    pub fn push(&mut self, x: T) {
+        let pos = self.count;
+        self.count += 1;
+        if let (Some(sum), Some(q)) = (self.sum.as_mut(), self.quantify) {
+            *sum += q(&x);
+        };
+        // an item replaces an extreme only when it compares beyond it, so NaNs are skipped, as by `minmax`
+        let newmin = match &self.min {
+            None => true,
+            Some(min) if x == *min => {
+                self.minlast = pos;
+                false
+            }
+            Some(min) => x < *min,
+        };
+        if newmin {
+            self.min = Some(x.clone());
+            self.minfirst = pos;
+            self.minlast = pos;
+        };
+        let newmax = match &self.max {
+            None => true,
+            Some(max) if x == *max => {
+                self.maxlast = pos;
+                false
+            }
+            Some(max) => x > *max,
+        };
+        if newmax {
+            self.max = Some(x);
+            self.maxfirst = pos;
+            self.maxlast = pos;
+        };
+    }
+
+    /// Combines with `other`, the accumulator of the items that followed those of self.
+    /// The positions of other are shifted to follow on.
+    /// The sum is kept only when both accumulated it, or when either of them is empty.
+    pub fn merge(&mut self, other: &Self) {
+        if other.count == 0 {
+            return;
+        };
+        if self.count == 0 {
+            *self = other.clone();
+            return;
+        };
+        let offset = self.count;
+        self.count += other.count;
+        self.sum = match (self.sum, other.sum) {
+            (Some(s1), Some(s2)) => Some(s1 + s2),
+            _ => None,
+        };
+        if let Some(omin) = &other.min {
+            let newmin = match &self.min {
+                None => true,
+                Some(min) if omin == min => {
+                    self.minlast = offset + other.minlast;
+                    false
+                }
+                Some(min) => omin < min,
+            };
+            if newmin {
+                self.min = Some(omin.clone());
+                self.minfirst = offset + other.minfirst;
+                self.minlast = offset + other.minlast;
+            };
+        };
+        if let Some(omax) = &other.max {
+            let newmax = match &self.max {
+                None => true,
+                Some(max) if omax == max => {
+                    self.maxlast = offset + other.maxlast;
+                    false
+                }
+                Some(max) => omax > max,
+            };
+            if newmax {
+                self.max = Some(omax.clone());
+                self.maxfirst = offset + other.maxfirst;
+                self.maxlast = offset + other.maxlast;
+            };
+        };
+    }
+
+    /// Difference of the quantified max and min, when quantify was given and items were seen
+    pub fn range(&self) -> Option<f64> {
+        match (&self.min, &self.max, self.quantify) {
+            (Some(min), Some(max), Some(q)) => Some(q(max) - q(min)),
+            _ => None,
+        }
+    }
+
+    /// Mean of the quantified items, when quantify was given and items were seen
+    pub fn mean(&self) -> Option<f64> {
+        self.sum
+            .filter(|_| self.count > 0)
+            .map(|sum| sum / self.count as f64)
+    }
+
+    /// The result as one-shot `MinMax` (with the first occurrences), None when no items were seen
+    pub fn minmax(&self) -> Option<MinMax<T>> {
+        match (&self.min, &self.max) {
+            (Some(min), Some(max)) => Some(MinMax {
+                min: min.clone(),
+                minindex: self.minfirst,
+                max: max.clone(),
+                maxindex: self.maxfirst,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl<T> Extend<T> for MinMaxStats<T>
+where
+    T: PartialOrd + Clone,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for x in iter {
+            self.push(x);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for MinMaxStats<T>
+where
+    T: PartialOrd + Clone,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut stats = MinMaxStats::new();
+        stats.extend(iter);
+        stats
+    }
+}
+
+/// Display implementation for MinMaxStats struct
+impl<T> std::fmt::Display for MinMaxStats<T>
+where
+    T: std::fmt::Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match (&self.min, &self.max) {
+            (Some(min), Some(max)) => write!(
+                f,
+                "count: {YL}{}{UN}, min: {GR}{}{UN} at {YL}{}..={}{UN}, max: {GR}{}{UN} at {YL}{}..={}{UN}",
+                self.count, min, self.minfirst, self.minlast, max, self.maxfirst, self.maxlast
+            )?,
+            _ => write!(f, "count: {YL}0{UN}")?,
+        };
+        match self.sum {
+            Some(sum) => write!(f, ", sum: {GR}{sum}{UN}"),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{Indices, Search};
+use core::{cmp::Ordering, cmp::Ordering::*, ops::Range};
+
+/// One column of a composite (multi-column) sort key:
+/// the column data, its comparator and its sort direction.
+pub struct KeyCol<'a, T, F>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    /// Column data, one item per row
+    pub col: &'a [T],
+    /// Comparator of two items of this column
+    pub c: F,
+    /// Sort direction of this column
+    pub ascending: bool,
+}
+
+/// Object safe comparisons of rows on one key column.
+/// Allows columns of different end types to be combined in one composite key.
+pub trait Key {
+    /// Number of rows in the column
+    fn rows(&self) -> usize;
+    /// Compares rows `i` and `j` of the column, in its sort direction
+    fn cmp_rows(&self, i: usize, j: usize) -> Ordering;
+}
+
+impl<'a, T, F> KeyCol<'a, T, F>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    /// New key column from data `col`, direction and comparator `c`
+    pub fn new(col: &'a [T], ascending: bool, c: F) -> Self {
+        KeyCol { col, c, ascending }
+    }
+
+    /// Closure comparing any row of this column against `target` value,
+    /// in the column's sort direction. One such probe is needed for
+    /// each component of a composite key passed to `multi_binsearch`.
+    pub fn probe<'b>(&'b self, target: &'b T) -> impl Fn(usize) -> Ordering + 'b {
+        move |row| {
+            if self.ascending {
+                (self.c)(&self.col[row], target)
+            } else {
+                (self.c)(target, &self.col[row])
+            }
+        }
+    }
+}
+
+impl<T, F> Key for KeyCol<'_, T, F>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    fn rows(&self) -> usize {
+        self.col.len()
+    }
+    fn cmp_rows(&self, i: usize, j: usize) -> Ordering {
+        if self.ascending {
+            (self.c)(&self.col[i], &self.col[j])
+        } else {
+            (self.c)(&self.col[j], &self.col[i])
+        }
+    }
+}
+
+/// Lexicographic comparison of rows `i` and `j` by all the `keys` in turn.
+/// The first key column that does not find them Equal decides.
+pub fn cmp_rows(keys: &[&dyn Key], i: usize, j: usize) -> Ordering {
+    for key in keys {
+        match key.cmp_rows(i, j) {
+            Equal => continue,
+            ord => return ord,
+        }
+    }
+    Equal
+}
+
+/// Stable sort index of table rows by the composite key `keys`
+/// (the most significant column first). Each column brings its own
+/// comparator and direction, e.g. (country asc, revenue desc, id asc).
+/// Rows equal on all the keys stay in their data order.
+pub fn multisort_indexed(keys: &[&dyn Key]) -> Vec<usize> {
+    let n = keys.first().map_or(0, |k| k.rows());
+    assert!(
+        keys.iter().all(|k| k.rows() == n),
+        "multisort_indexed: key columns differ in length"
+    );
+    let mut idx = <&[usize]>::newindex(n);
+    idx.sort_by(|&i, &j| cmp_rows(keys, i, j));
+    idx
+}
+
+/// Ranks of table rows by the composite key `keys`, obtained by inverting `multisort_indexed`.
+/// When ascending is false, the ranks are complemented (rank 0 goes to the last row in the key order).
+pub fn multirank(keys: &[&dyn Key], ascending: bool) -> Vec<usize> {
+    let ranks = multisort_indexed(keys).invindex();
+    if ascending {
+        ranks
+    } else {
+        ranks.complindex()
+    }
+}
+
+/// Binary search of sort index `idx` (as produced by `multisort_indexed`) for a composite key.
+/// `probes` compare a row against the key components in turn (see `KeyCol::probe`).
+/// Fewer probes than key columns search by the key prefix.
+/// Returns the range of sort positions of all matching rows,
+/// or the empty insert position range when there is no match.
+pub fn multi_binsearch(idx: &[usize], probes: &[&dyn Fn(usize) -> Ordering]) -> Range<usize> {
+    if idx.is_empty() {
+        return 0..0;
+    };
+    (0..=idx.len() - 1).binary_all(|pos| {
+        for probe in probes {
+            match probe(idx[pos]) {
+                Equal => continue,
+                ord => return ord,
+            }
+        }
+        Equal
+    })
+}
@@ -1,4 +1,7 @@
-use crate::{Mutops, Vecops};
+use crate::{
+    random::splitmix64,
+    vecops::{hashrange, robust_hashsort, HASHDEPTH},
+    Mutops, Pivot, Search, Vecops};
 use core::cmp::{Ordering, Ordering::*};
 use core::ops::Range;
 
@@ -181,17 +184,47 @@ impl<T> Mutops<T> for &mut [T] {
             self.sort_unstable_by(|a, b| quantify(a).total_cmp(&quantify(b)));
             return;
         };
-        let (min, max) = self.minmaxt();
-        self.muthashsortslice(0, n, quantify(&min), quantify(&max), quantify);
+        match hashrange(self, quantify) {
+            Some((min, max)) => self.muthashsortslice(0, n, min, max, quantify),
+            None => self.muthashsort_stable(quantify),
+        };
+    }
+
+    /// Stable version of `muthashsort`, robust to skewed, clustered and infinite data.
+    /// Sorts an index by the quantile bucketed hash sort of `Vecops::hashsort_robust_indexed`,
+    /// then applies it to self by following the cycles of the permutation,
+    /// so the items are only swapped, never cloned. Not in place: the index,
+    /// its buckets and the cycle marks take O(n) auxiliary memory.
+    fn muthashsort_stable(self, quantify: impl Copy + Fn(&T) -> f64)
+    where
+        T: PartialOrd,
+    {
+        let n = self.len();
+        let mut idx = Vec::from_iter(0..n);
+        robust_hashsort(self, &mut idx, quantify, HASHDEPTH);
+        let mut done = vec![false; n];
+        for start in 0..n {
+            if done[start] {
+                continue;
+            };
+            // position k receives the item from idx[k]
+            let mut k = start;
+            done[k] = true;
+            while idx[k] != start {
+                self.swap(k, idx[k]);
+                k = idx[k];
+                done[k] = true;
+            }
+        }
     }
 
-    /// Mutable insert logsort. Pass in reversed comparator `c` for descending sort
+    /// Mutable insert logsort of `rng` within self. Pass in reversed comparator `c` for descending sort.
+    /// Stable. Items are rotated into place, so they need not be `Copy`.
     fn mutisort<F>(self, rng: Range<usize>, c: F)
     where
-        T: Copy,
         F: Fn(&T, &T) -> Ordering,
     {
-        if self.len() < 2 {
+        if rng.len() < 2 {
             return;
         };
         if c(&self[rng.start + 1], &self[rng.start]) == Less {
@@ -199,14 +232,323 @@ impl<T> Mutops<T> for &mut [T] {
         };
         for i in rng.start + 2..rng.end {
             if c(&self[i], &self[i - 1]) == Less {
-                let target = self[i];
-                let insert = match self[rng.start..i - 1].binary_search_by(|j| c(j, &target)) {
-                    Ok(ins) => ins + 1,
-                    Err(ins) => ins, // *ins when using Search::binary_by()
-                };
-                self.copy_within(insert..i, insert + 1);
-                self[insert] = target;
+                let insert = rng.start
+                    + match self[rng.start..i - 1].binary_search_by(|j| c(j, &self[i])) {
+                        Ok(ins) => ins + 1,
+                        Err(ins) => ins, // *ins when using Search::binary_by()
+                    };
+                self[insert..=i].rotate_right(1);
+            };
+        }
+    }
+
+    /// In-place introspective sort by comparator `c`.
+    /// Recursively applies `mutpartition` (with `Pivot::Ninther` for long ranges, `Pivot::MedianOf3` otherwise),
+    /// so that runs of equal items are set aside after one pass.
+    /// It uses `mutpartition` rather than `part`, because `part` partitions a slice of references `&mut [&T]`
+    /// and leaves the equal part undefined, whereas here the items themselves are rearranged in self.
+    /// Short ranges are finished by `mutisort` and ranges found already sorted are skipped.
+    /// When the recursion gets too deep (adversarial data), falls back to heapsort,
+    /// which guarantees O(n log n) worst case. Needs no extra memory, no `Clone` and no numeric keys.
+    /// Not stable. Pass in reversed comparator `c` for descending sort.
+    fn mutsort_by<F>(self, c: F)
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        let n = self.len();
+        // depth limit of 2 log2(n)
+        let depth = 2 * (usize::BITS - n.leading_zeros()) as usize;
+        introsort(self, 0..n, depth, &c);
+    }
+
+    /// Rearranges self into the lexicographically next greater permutation, in place.
+    /// Repeated items are allowed, each distinct arrangement is then visited once.
+    /// Returns false when self was already the last (descending) permutation,
+    /// in which case it is reset to the first (ascending) one.
+    /// Starting from ascending order, repeated calls enumerate all the permutations.
+    fn next_permutation(self) -> bool
+    where
+        T: PartialOrd,
+    {
+        step_permutation(self, |a, b| a < b)
+    }
+
+    /// Rearranges self into the lexicographically next lesser permutation, in place.
+    /// Returns false when self was already the first (ascending) permutation,
+    /// in which case it is reset to the last (descending) one.
+    fn prev_permutation(self) -> bool
+    where
+        T: PartialOrd,
+    {
+        step_permutation(self, |a, b| a > b)
+    }
+
+    /// Three-way (Dutch national flag) partition of `rng` within self, in place.
+    /// The pivot value is chosen by the `pivot` strategy, see `Pivot`.
+    /// Returns the boundaries `(lt_end, gt_start)`, such that
+    /// `rng.start..lt_end` holds the items lesser than the pivot,
+    /// `lt_end..gt_start` the items equal to it (may be empty for `Pivot::Value`)
+    /// and `gt_start..rng.end` the greater items.
+    /// Not stable: equal items may change their order.
+    fn mutpartition(
+        self,
+        rng: &Range<usize>,
+        pivot: &Pivot<T>,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> (usize, usize) {
+        if rng.is_empty() {
+            return (rng.start, rng.start);
+        };
+        let mut ltend = rng.start;
+        let mut i = rng.start;
+        let mut gtstart = rng.end;
+        if let Some(pos) = pivot_pos(self, rng, pivot, c) {
+            // the pivot item leads the equal part, so it is never cloned
+            self.swap(rng.start, pos);
+            i += 1;
+        };
+        while i < gtstart {
+            let ord = match pivot {
+                Pivot::Value(p) => c(&self[i], p),
+                _ => c(&self[i], &self[ltend]),
+            };
+            match ord {
+                Less => {
+                    self.swap(ltend, i);
+                    ltend += 1;
+                    i += 1;
+                }
+                Greater => {
+                    gtstart -= 1;
+                    self.swap(i, gtstart);
+                }
+                Equal => i += 1,
+            }
+        }
+        (ltend, gtstart)
+    }
+
+    /// Stable version of `mutpartition`: the items keep their original order
+    /// within each of the three parts. Unlike `mutpartition`, it is not in place:
+    /// the items of `rng` are cloned into temporary buffers, taking O(n) extra memory,
+    /// and then written back in their partitioned order.
+    fn mutpartition_stable(
+        self,
+        rng: &Range<usize>,
+        pivot: &Pivot<T>,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> (usize, usize)
+    where
+        T: Clone,
+    {
+        if rng.is_empty() {
+            return (rng.start, rng.start);
+        };
+        let p = pivot_value(self, rng, pivot, c);
+        let mut eqset: Vec<T> = Vec::new();
+        let mut gtset: Vec<T> = Vec::new();
+        let mut ltend = rng.start;
+        for i in rng.clone() {
+            match c(&self[i], &p) {
+                Less => {
+                    if i > ltend {
+                        self[ltend] = self[i].clone();
+                    };
+                    ltend += 1;
+                }
+                Equal => eqset.push(self[i].clone()),
+                Greater => gtset.push(self[i].clone()),
+            }
+        }
+        let gtstart = ltend + eqset.len();
+        self[ltend..gtstart].clone_from_slice(&eqset);
+        self[gtstart..rng.end].clone_from_slice(&gtset);
+        (ltend, gtstart)
+    }
+
+    /// Stable multi-pivot partition of `rng` into `splitters.len()+1` buckets.
+    /// The splitters must be in ascending order of comparator `c`.
+    /// Bucket j receives the items `x` such that `splitters[j-1] <= x < splitters[j]`
+    /// (the first and the last buckets are open ended).
+    /// Returns the bucket boundaries: `rng.start`, the start of each following bucket and `rng.end`,
+    /// so that bucket j occupies `bounds[j]..bounds[j+1]`.
+    /// Not in place: the items of `rng` are cloned into one temporary buffer per bucket,
+    /// taking O(n) extra memory, and then written back bucket by bucket.
+    fn mutbuckets(
+        self,
+        rng: &Range<usize>,
+        splitters: &[T],
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Vec<usize>
+    where
+        T: Clone,
+    {
+        let k = splitters.len();
+        let mut buckets: Vec<Vec<T>> = vec![Vec::new(); k + 1];
+        for item in self.iter().take(rng.end).skip(rng.start) {
+            // the number of splitters not greater than the item is its bucket
+            let b = if k == 0 {
+                0
+            } else {
+                match (0..=k - 1).binary_by(|j| match c(&splitters[j], item) {
+                    Greater => Greater,
+                    _ => Less,
+                }) {
+                    Ok(b) | Err(b) => b,
+                }
             };
+            buckets[b].push(item.clone());
+        }
+        let mut bounds = Vec::with_capacity(k + 2);
+        let mut isub = rng.start;
+        bounds.push(isub);
+        for bucket in buckets {
+            self[isub..isub + bucket.len()].clone_from_slice(&bucket);
+            isub += bucket.len();
+            bounds.push(isub);
         }
+        bounds
+    }
+}
+
+/// Subscript of the median of the three items at i0, i1, i2
+fn median3<T>(s: &[T], i0: usize, i1: usize, i2: usize, c: &mut impl FnMut(&T, &T) -> Ordering) -> usize {
+    let (lo, hi) = if c(&s[i1], &s[i0]) == Less { (i1, i0) } else { (i0, i1) };
+    if c(&s[i2], &s[lo]) == Less {
+        lo
+    } else if c(&s[hi], &s[i2]) == Less {
+        hi
+    } else {
+        i2
+    }
+}
+
+/// Next permutation of `s` in the order given by `lt`, see `next_permutation`
+fn step_permutation<T>(s: &mut [T], lt: impl Fn(&T, &T) -> bool) -> bool {
+    // the longest non-increasing suffix starts at i
+    let mut i = s.len();
+    while i > 1 && !lt(&s[i - 2], &s[i - 1]) {
+        i -= 1;
+    }
+    if i <= 1 {
+        s.reverse();
+        return false;
+    };
+    // swap the item before the suffix with the last item of the suffix that exceeds it
+    let mut j = s.len() - 1;
+    while !lt(&s[i - 2], &s[j]) {
+        j -= 1;
+    }
+    s.swap(i - 2, j);
+    s[i - 1..].reverse();
+    true
+}
+
+/// Subscript of the pivot item within non-empty range `rng` of `s`, selected by the `pivot` strategy.
+/// None for `Pivot::Value`, which need not be in `s`.
+fn pivot_pos<T>(
+    s: &[T],
+    rng: &Range<usize>,
+    pivot: &Pivot<T>,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> Option<usize> {
+    let n = rng.len();
+    let (first, mid, last) = (rng.start, rng.start + n / 2, rng.end - 1);
+    let pos = match pivot {
+        Pivot::Value(_) => return None,
+        Pivot::Random(seed) => rng.start + (splitmix64(*seed) % n as u64) as usize,
+        Pivot::Ninther if n >= 9 => {
+            let step = n / 8;
+            let m1 = median3(s, first, first + step, first + 2 * step, c);
+            let m2 = median3(s, mid - step, mid, mid + step, c);
+            let m3 = median3(s, last - 2 * step, last - step, last, c);
+            median3(s, m1, m2, m3, c)
+        }
+        _ => median3(s, first, mid, last, c),
+    };
+    Some(pos)
+}
+
+/// Pivot value for non-empty range `rng` of `s`, selected by the `pivot` strategy
+fn pivot_value<T>(
+    s: &[T],
+    rng: &Range<usize>,
+    pivot: &Pivot<T>,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> T
+where
+    T: Clone,
+{
+    match (pivot_pos(s, rng, pivot, c), pivot) {
+        (None, Pivot::Value(p)) => p.clone(),
+        (pos, _) => s[pos.expect("pivot_value: missing pivot")].clone(),
+    }
+}
+
+/// Recursive body of `mutsort_by`: sorts `rng` of `s`, allowing `depth` more partitioning levels
+fn introsort<T, F>(s: &mut [T], mut rng: Range<usize>, mut depth: usize, c: &F)
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    loop {
+        if rng.len() <= 16 {
+            s.mutisort(rng, c);
+            return;
+        };
+        // already sorted (or equal) ranges are detected in one pass
+        if (rng.start + 1..rng.end).all(|i| c(&s[i], &s[i - 1]) != Less) {
+            return;
+        };
+        if depth == 0 {
+            heapsort(&mut s[rng], c);
+            return;
+        };
+        depth -= 1;
+        let pivot = if rng.len() > 128 { Pivot::Ninther } else { Pivot::MedianOf3 };
+        let (ltend, gtstart) = s.mutpartition(&rng, &pivot, &mut |a, b| c(a, b));
+        // recurse into the shorter part, iterate over the longer one
+        if ltend - rng.start < rng.end - gtstart {
+            introsort(s, rng.start..ltend, depth, c);
+            rng = gtstart..rng.end;
+        } else {
+            introsort(s, gtstart..rng.end, depth, c);
+            rng = rng.start..ltend;
+        }
+    }
+}
+
+/// In-place heapsort of `s` by comparator `c`, the worst case fallback of `introsort`
+fn heapsort<T, F>(s: &mut [T], c: &F)
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    let n = s.len();
+    for root in (0..n / 2).rev() {
+        siftdown(s, root, n, c);
+    }
+    for end in (1..n).rev() {
+        s.swap(0, end);
+        siftdown(s, 0, end, c);
+    }
+}
+
+/// Restores the max-heap property of `s[..end]` below `root`
+fn siftdown<T, F>(s: &mut [T], mut root: usize, end: usize, c: &F)
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    loop {
+        let mut child = 2 * root + 1;
+        if child >= end {
+            return;
+        };
+        if child + 1 < end && c(&s[child], &s[child + 1]) == Less {
+            child += 1;
+        };
+        if c(&s[root], &s[child]) != Less {
+            return;
+        };
+        s.swap(root, child);
+        root = child;
     }
 }
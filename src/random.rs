@@ -0,0 +1,115 @@
+use crate::{Indices, Vecops};
+
+/// Golden ratio increment of the splitmix64 sequence
+const GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Splitmix64 hash, used to turn seeds into pseudo random numbers
+pub(crate) fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(GAMMA);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Small built-in seeded pseudo random generator (splitmix64).
+/// Fast and reproducible: the same seed always gives the same sequence.
+/// Not suitable for cryptography.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// New generator from `seed`
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    /// Next pseudo random u64
+    pub fn next_u64(&mut self) -> u64 {
+        let r = splitmix64(self.state);
+        self.state = self.state.wrapping_add(GAMMA);
+        r
+    }
+
+    /// Unbiased pseudo random usize in `0..n`, by Lemire's multiply and reject method.
+    /// Panics when n is zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "Rng::below: empty range");
+        let n = n as u64;
+        let mut m = self.next_u64() as u128 * n as u128;
+        if (m as u64) < n {
+            let threshold = n.wrapping_neg() % n;
+            while (m as u64) < threshold {
+                m = self.next_u64() as u128 * n as u128;
+            }
+        };
+        (m >> 64) as usize
+    }
+}
+
+/// Fisher-Yates shuffle of `v` in place, reproducible from `seed`
+pub fn shuffle<T>(v: &mut [T], seed: u64) {
+    let mut rng = Rng::new(seed);
+    for i in (1..v.len()).rev() {
+        v.swap(i, rng.below(i + 1));
+    }
+}
+
+/// Random permutation of `0..n`: the shuffled identity index (see `Indices::newindex`).
+/// Usable with `unindex` to shuffle a copy of any data, or as random ranks.
+pub fn random_permutation(n: usize, seed: u64) -> Vec<usize> {
+    let mut idx = <&[usize]>::newindex(n);
+    shuffle(&mut idx, seed);
+    idx
+}
+
+/// Reservoir sampling: up to `k` items drawn uniformly without replacement
+/// from an iterator of unknown length, in one pass and O(k) memory.
+/// When the iterator yields no more than k items, they are all returned, in their order.
+pub fn reservoir<I>(iter: I, k: usize, seed: u64) -> Vec<I::Item>
+where
+    I: IntoIterator,
+{
+    let mut rng = Rng::new(seed);
+    let mut sample = Vec::with_capacity(k);
+    for (i, item) in iter.into_iter().enumerate() {
+        if i < k {
+            sample.push(item);
+        } else {
+            let j = rng.below(i + 1);
+            if j < k {
+                sample[j] = item;
+            };
+        };
+    }
+    sample
+}
+
+/// Ascending subscripts of `k` (at most `n`) items sampled from `0..n` without replacement
+pub fn sample_indices(n: usize, k: usize, seed: u64) -> Vec<usize> {
+    let mut sample = reservoir(0..n, k, seed);
+    sample.sort_unstable();
+    sample
+}
+
+/// Stratified sampling: up to `k` subscripts sampled without replacement from each group
+/// of equal values (stratum) of `v`. The groups are found by `Vecops::group_by_index`
+/// and come in ascending order of their values, the sampled subscripts ascending within each.
+pub fn stratified_sample<T>(v: &[T], k: usize, seed: u64) -> Vec<(T, Vec<usize>)>
+where
+    T: PartialOrd + Clone,
+{
+    if v.is_empty() {
+        return Vec::new();
+    };
+    let mut rng = Rng::new(seed);
+    v.group_by_index(&v.mergesort_indexed())
+        .into_iter()
+        .map(|(val, group)| {
+            let mut sample = reservoir(group, k, rng.next_u64());
+            sample.sort_unstable();
+            (val, sample)
+        })
+        .collect()
+}
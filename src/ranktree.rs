@@ -0,0 +1,208 @@
+use crate::Vecops;
+
+/// Fenwick (binary indexed) tree of counts over positions `0..n`.
+/// Counts the items present at each position (typically a rank),
+/// with O(log n) insertion, removal, prefix counts and k-th present position.
+#[derive(Clone, Debug)]
+pub(crate) struct Fenwick {
+    /// 1-based implicit tree of partial counts
+    tree: Vec<usize>,
+}
+
+impl Fenwick {
+    /// Empty tree over positions `0..n`
+    pub(crate) fn new(n: usize) -> Self {
+        Fenwick {
+            tree: vec![0; n + 1],
+        }
+    }
+
+    /// Tree over the given counts of items at positions `0..counts.len()`, built in O(n)
+    pub(crate) fn from_counts(counts: &[usize]) -> Self {
+        let n = counts.len();
+        let mut tree = vec![0; n + 1];
+        tree[1..].copy_from_slice(counts);
+        for i in 1..=n {
+            let parent = i + (i & i.wrapping_neg());
+            if parent <= n {
+                tree[parent] += tree[i];
+            };
+        }
+        Fenwick { tree }
+    }
+
+    /// Adds one item at position `pos`
+    pub(crate) fn insert(&mut self, pos: usize) {
+        let mut i = pos + 1;
+        while i < self.tree.len() {
+            self.tree[i] += 1;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Removes one item from position `pos`, which must be present
+    pub(crate) fn remove(&mut self, pos: usize) {
+        let mut i = pos + 1;
+        while i < self.tree.len() {
+            self.tree[i] -= 1;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Number of items at positions lesser than `pos`
+    pub(crate) fn prefix(&self, pos: usize) -> usize {
+        let mut count = 0;
+        let mut i = pos.min(self.tree.len() - 1);
+        while i > 0 {
+            count += self.tree[i];
+            i -= i & i.wrapping_neg();
+        }
+        count
+    }
+
+    /// Position of the k-th (from 0) present item, found by binary descent of the tree.
+    /// Returns n when there are not more than k items.
+    pub(crate) fn kth(&self, k: usize) -> usize {
+        let n = self.tree.len() - 1;
+        let mut pos = 0;
+        let mut rem = k + 1;
+        let mut step = if n == 0 { 0 } else { 1 << n.ilog2() };
+        while step > 0 {
+            if pos + step <= n && self.tree[pos + step] < rem {
+                pos += step;
+                rem -= self.tree[pos];
+            };
+            step >>= 1;
+        }
+        pos
+    }
+}
+
+/// Dynamic ranks and order statistics over a fixed domain of values.
+/// Keeps the count of each domain value in a Fenwick tree, so that
+/// `insert`, `remove`, `rank`, `kth` and `count_between` all take O(log n).
+/// Seeded from data and its sort index, e.g. a leaderboard, it is then maintained
+/// online, without re-ranking everything after each change.
+#[derive(Clone, Debug)]
+pub struct RankTree<T> {
+    /// Sorted distinct values of the domain
+    keys: Vec<T>,
+    counts: Fenwick,
+    len: usize,
+}
+
+impl<T> RankTree<T>
+where
+    T: PartialOrd + Clone,
+{
+    /// Tree holding all the items of `v`, seeded from its ascending sort index `idx`
+    /// (e.g. from `mergesort_indexed`). The domain is the distinct values of `v`.
+    pub fn from_sortindex(v: &[T], idx: &[usize]) -> Self {
+        let mut keys: Vec<T> = Vec::new();
+        let mut counts: Vec<usize> = Vec::new();
+        for &i in idx {
+            match keys.last() {
+                Some(last) if *last == v[i] => *counts.last_mut().expect("from_sortindex: no counts") += 1,
+                _ => {
+                    keys.push(v[i].clone());
+                    counts.push(1);
+                }
+            };
+        }
+        RankTree {
+            keys,
+            counts: Fenwick::from_counts(&counts),
+            len: idx.len(),
+        }
+    }
+
+    /// Tree holding all the items of `v`, sorted by `mergesort_indexed`
+    pub fn new(v: &[T]) -> Self {
+        if v.is_empty() {
+            return RankTree {
+                keys: Vec::new(),
+                counts: Fenwick::new(0),
+                len: 0,
+            };
+        };
+        Self::from_sortindex(v, &v.mergesort_indexed())
+    }
+
+    /// Empty tree over the domain of the distinct values of `domain`
+    pub fn with_domain(domain: &[T]) -> Self {
+        let mut tree = Self::new(domain);
+        tree.counts = Fenwick::new(tree.keys.len());
+        tree.len = 0;
+        tree
+    }
+
+    /// Number of items held
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// True when no items are held
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The sorted distinct values of the domain
+    pub fn domain(&self) -> &[T] {
+        &self.keys
+    }
+
+    /// Position of `x` in the domain, if it is there
+    fn find(&self, x: &T) -> Option<usize> {
+        let pos = self.keys.partition_point(|k| k < x);
+        (pos < self.keys.len() && self.keys[pos] == *x).then_some(pos)
+    }
+
+    /// Inserts one item `x`. Returns false (and does nothing) when `x` is not in the domain.
+    pub fn insert(&mut self, x: &T) -> bool {
+        match self.find(x) {
+            Some(pos) => {
+                self.counts.insert(pos);
+                self.len += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes one item `x`. Returns false (and does nothing) when there is none.
+    pub fn remove(&mut self, x: &T) -> bool {
+        match self.find(x) {
+            Some(pos) if self.counts.prefix(pos + 1) > self.counts.prefix(pos) => {
+                self.counts.remove(pos);
+                self.len -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Number of items equal to `x`
+    pub fn count(&self, x: &T) -> usize {
+        self.find(x)
+            .map_or(0, |pos| self.counts.prefix(pos + 1) - self.counts.prefix(pos))
+    }
+
+    /// Rank of `x`: the number of items lesser than it. `x` need not be in the domain.
+    /// The descending rank (e.g. leaderboard position from 0) is `len() - rank(x) - count(x)`.
+    pub fn rank(&self, x: &T) -> usize {
+        self.counts.prefix(self.keys.partition_point(|k| k < x))
+    }
+
+    /// Number of items in the half open range of values `[a, b)`
+    pub fn count_between(&self, a: &T, b: &T) -> usize {
+        self.rank(b).saturating_sub(self.rank(a))
+    }
+
+    /// The k-th (from 0) smallest item, None when there are not more than k items
+    pub fn kth(&self, k: usize) -> Option<&T> {
+        if k >= self.len {
+            return None;
+        };
+        self.keys.get(self.counts.kth(k))
+    }
+}
@@ -0,0 +1,198 @@
+use crate::MinMax;
+use core::ops::Range;
+
+/// Sparse table for O(1) range minimum and maximum queries over an immutable slice.
+/// Preprocessing takes O(n log n) time and space (of subscripts only, the data is borrowed).
+/// Level k holds the subscripts of the extremes of all the ranges of length 2^k,
+/// any query range is then covered by two overlapping such ranges.
+/// Of equal extremes, the first occurrence is reported, as by `Vecops::minmax_slice`.
+pub struct SparseTable<'a, T> {
+    data: &'a [T],
+    mins: Vec<Vec<usize>>,
+    maxs: Vec<Vec<usize>>,
+}
+
+impl<'a, T> SparseTable<'a, T>
+where
+    T: PartialOrd + Clone,
+{
+    /// Preprocesses `data`
+    pub fn new(data: &'a [T]) -> Self {
+        let n = data.len();
+        let mut mins = vec![Vec::from_iter(0..n)];
+        let mut maxs = mins.clone();
+        let mut len = 1;
+        while 2 * len <= n {
+            let (prevmins, prevmaxs) = (&mins[mins.len() - 1], &maxs[maxs.len() - 1]);
+            let levelmins = (0..=n - 2 * len)
+                .map(|i| firstmin(data, prevmins[i], prevmins[i + len]))
+                .collect();
+            let levelmaxs = (0..=n - 2 * len)
+                .map(|i| firstmax(data, prevmaxs[i], prevmaxs[i + len]))
+                .collect();
+            mins.push(levelmins);
+            maxs.push(levelmaxs);
+            len *= 2;
+        }
+        SparseTable { data, mins, maxs }
+    }
+
+    /// The data slice
+    pub fn data(&self) -> &'a [T] {
+        self.data
+    }
+
+    /// Subscript of the (first) minimum in non-empty range `rng`, O(1)
+    pub fn argmin(&self, rng: Range<usize>) -> usize {
+        let (k, left, right) = self.cover(&rng);
+        firstmin(self.data, self.mins[k][left], self.mins[k][right])
+    }
+
+    /// Subscript of the (first) maximum in non-empty range `rng`, O(1)
+    pub fn argmax(&self, rng: Range<usize>) -> usize {
+        let (k, left, right) = self.cover(&rng);
+        firstmax(self.data, self.maxs[k][left], self.maxs[k][right])
+    }
+
+    /// MinMax of non-empty range `rng`, O(1)
+    pub fn minmax(&self, rng: Range<usize>) -> MinMax<T> {
+        let minindex = self.argmin(rng.clone());
+        let maxindex = self.argmax(rng);
+        MinMax {
+            min: self.data[minindex].clone(),
+            minindex,
+            max: self.data[maxindex].clone(),
+            maxindex,
+        }
+    }
+
+    /// Level and starts of the two ranges of length 2^level that cover `rng`
+    fn cover(&self, rng: &Range<usize>) -> (usize, usize, usize) {
+        assert!(
+            !rng.is_empty() && rng.end <= self.data.len(),
+            "SparseTable: invalid query range {rng:?}"
+        );
+        let k = rng.len().ilog2() as usize;
+        (k, rng.start, rng.end - (1 << k))
+    }
+}
+
+/// Segment tree for O(log n) range minimum and maximum queries, with O(log n) updates.
+/// Holds its own copy of the data, which can then be updated by `update`.
+/// Of equal extremes, the first occurrence is reported, as by `Vecops::minmax_slice`.
+pub struct SegTree<T> {
+    data: Vec<T>,
+    /// Subscripts of (min,max) of each node's range, the root node is 1
+    nodes: Vec<(usize, usize)>,
+}
+
+impl<T> SegTree<T>
+where
+    T: PartialOrd + Clone,
+{
+    /// Builds the tree over a copy of `data`
+    pub fn new(data: &[T]) -> Self {
+        let n = data.len();
+        let mut tree = SegTree {
+            data: data.to_vec(),
+            nodes: vec![(0, 0); 4 * n.max(1)],
+        };
+        if n > 0 {
+            tree.build(1, 0..n);
+        };
+        tree
+    }
+
+    /// The current data
+    pub fn data(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Replaces the item at `pos` with `value` and updates the tree, O(log n)
+    pub fn update(&mut self, pos: usize, value: T) {
+        self.data[pos] = value;
+        let n = self.data.len();
+        self.refresh(1, 0..n, pos);
+    }
+
+    /// MinMax of non-empty range `rng`, O(log n)
+    pub fn minmax(&self, rng: Range<usize>) -> MinMax<T> {
+        assert!(
+            !rng.is_empty() && rng.end <= self.data.len(),
+            "SegTree: invalid query range {rng:?}"
+        );
+        let (minindex, maxindex) = self
+            .query(1, 0..self.data.len(), &rng)
+            .expect("SegTree: empty query");
+        MinMax {
+            min: self.data[minindex].clone(),
+            minindex,
+            max: self.data[maxindex].clone(),
+            maxindex,
+        }
+    }
+
+    /// Combines the extremes of two adjacent ranges, `a` before `b`
+    fn combine(&self, a: (usize, usize), b: (usize, usize)) -> (usize, usize) {
+        (firstmin(&self.data, a.0, b.0), firstmax(&self.data, a.1, b.1))
+    }
+
+    fn build(&mut self, node: usize, span: Range<usize>) {
+        if span.len() == 1 {
+            self.nodes[node] = (span.start, span.start);
+            return;
+        };
+        let mid = span.start + span.len() / 2;
+        self.build(2 * node, span.start..mid);
+        self.build(2 * node + 1, mid..span.end);
+        self.nodes[node] = self.combine(self.nodes[2 * node], self.nodes[2 * node + 1]);
+    }
+
+    fn refresh(&mut self, node: usize, span: Range<usize>, pos: usize) {
+        if span.len() == 1 {
+            return;
+        };
+        let mid = span.start + span.len() / 2;
+        if pos < mid {
+            self.refresh(2 * node, span.start..mid, pos);
+        } else {
+            self.refresh(2 * node + 1, mid..span.end, pos);
+        };
+        self.nodes[node] = self.combine(self.nodes[2 * node], self.nodes[2 * node + 1]);
+    }
+
+    fn query(&self, node: usize, span: Range<usize>, rng: &Range<usize>) -> Option<(usize, usize)> {
+        if rng.end <= span.start || span.end <= rng.start {
+            return None;
+        };
+        if rng.start <= span.start && span.end <= rng.end {
+            return Some(self.nodes[node]);
+        };
+        let mid = span.start + span.len() / 2;
+        match (
+            self.query(2 * node, span.start..mid, rng),
+            self.query(2 * node + 1, mid..span.end, rng),
+        ) {
+            (Some(a), Some(b)) => Some(self.combine(a, b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+/// Subscript of the lesser of two items, `i` preceding `j`, the first one when equal
+fn firstmin<T: PartialOrd>(data: &[T], i: usize, j: usize) -> usize {
+    if data[j] < data[i] {
+        j
+    } else {
+        i
+    }
+}
+
+/// Subscript of the greater of two items, `i` preceding `j`, the first one when equal
+fn firstmax<T: PartialOrd>(data: &[T], i: usize, j: usize) -> usize {
+    if data[j] > data[i] {
+        j
+    } else {
+        i
+    }
+}
@@ -0,0 +1,644 @@
+use crate::{Indices, Join, Search, Setops};
+use core::cmp::Ordering::*;
+
+impl<'a, T> Setops<'a, T> for &'a [T] {
+    /// General set operation on two ascending sorted slices.
+    /// Every distinct item is output `f(c1,c2)` times, where c1 and c2
+    /// are its multiplicities in self and v2 respectively. For example:
+    /// `|c1,c2| c1+c2` is the multiset sum (same as `merge`),
+    /// `|c1,c2| c1.min(c2)` is the multiset intersection.
+    fn setop(self, v2: &[T], f: impl Fn(usize, usize) -> usize) -> Vec<T>
+    where
+        T: PartialOrd + Clone,
+    {
+        setop_by(|i| &self[i], self.len(), |i| &v2[i], v2.len(), f)
+    }
+
+    /// Union of two ascending sorted slices.
+    /// Sets: each distinct item of either once.
+    /// Multisets: each item as many times as it occurs in one of them at most.
+    fn union(self, v2: &[T], multi: bool) -> Vec<T>
+    where
+        T: PartialOrd + Clone,
+    {
+        self.setop(v2, union_rule(multi))
+    }
+
+    /// Intersection of two ascending sorted slices.
+    /// Sets: each item present in both once.
+    /// Multisets: each item as many times as it occurs in both at least.
+    fn intersection(self, v2: &[T], multi: bool) -> Vec<T>
+    where
+        T: PartialOrd + Clone,
+    {
+        self.setop(v2, intersection_rule(multi))
+    }
+
+    /// Difference of two ascending sorted slices: self - v2.
+    /// Sets: each item of self that is not in v2 at all, once.
+    /// Multisets: the occurrences in v2 cancel out as many in self.
+    fn difference(self, v2: &[T], multi: bool) -> Vec<T>
+    where
+        T: PartialOrd + Clone,
+    {
+        self.setop(v2, difference_rule(multi))
+    }
+
+    /// Symmetric difference of two ascending sorted slices.
+    /// Sets: each item present in exactly one of them, once.
+    /// Multisets: the absolute difference of the multiplicities.
+    fn symdiff(self, v2: &[T], multi: bool) -> Vec<T>
+    where
+        T: PartialOrd + Clone,
+    {
+        self.setop(v2, symdiff_rule(multi))
+    }
+
+    /// True when every item of self is also in v2
+    /// (multisets: at least as many times). Stops at the first counterexample.
+    fn is_subset(self, v2: &[T], multi: bool) -> bool
+    where
+        T: PartialOrd,
+    {
+        subset_by(|i| &self[i], self.len(), |i| &v2[i], v2.len(), multi)
+    }
+
+    /// True when self and v2 have no items in common.
+    /// Stops at the first common item.
+    fn is_disjoint(self, v2: &[T]) -> bool
+    where
+        T: PartialOrd,
+    {
+        disjoint_by(|i| &self[i], self.len(), |i| &v2[i], v2.len())
+    }
+
+    /// Jaccard similarity coefficient of two ascending sorted slices,
+    /// i.e. the size of their intersection divided by the size of their union.
+    /// Multisets use the sums of the minimum and the maximum multiplicities.
+    /// Two empty slices are similar (1.0).
+    fn jaccard(self, v2: &[T], multi: bool) -> f64
+    where
+        T: PartialOrd,
+    {
+        jaccard_by(|i| &self[i], self.len(), |i| &v2[i], v2.len(), multi)
+    }
+
+    /// Zero-clone version of `setop`.
+    /// Returns references to the items of self (or of v2, for items not in self).
+    fn setop_refs(self, v2: &'a [T], f: impl Fn(usize, usize) -> usize) -> Vec<&'a T>
+    where
+        T: PartialOrd,
+    {
+        setop_refs_by(|i| &self[i], self.len(), |i| &v2[i], v2.len(), f)
+    }
+
+    /// Zero-clone version of `union`
+    fn union_refs(self, v2: &'a [T], multi: bool) -> Vec<&'a T>
+    where
+        T: PartialOrd,
+    {
+        self.setop_refs(v2, union_rule(multi))
+    }
+
+    /// Zero-clone version of `intersection`
+    fn intersection_refs(self, v2: &'a [T], multi: bool) -> Vec<&'a T>
+    where
+        T: PartialOrd,
+    {
+        self.setop_refs(v2, intersection_rule(multi))
+    }
+
+    /// Zero-clone version of `difference`
+    fn difference_refs(self, v2: &'a [T], multi: bool) -> Vec<&'a T>
+    where
+        T: PartialOrd,
+    {
+        self.setop_refs(v2, difference_rule(multi))
+    }
+
+    /// Zero-clone version of `symdiff`
+    fn symdiff_refs(self, v2: &'a [T], multi: bool) -> Vec<&'a T>
+    where
+        T: PartialOrd,
+    {
+        self.setop_refs(v2, symdiff_rule(multi))
+    }
+
+    /// Zero-clone version of `setop_indexed`
+    fn setop_indexed_refs(
+        self,
+        ix1: &[usize],
+        v2: &'a [T],
+        ix2: &[usize],
+        f: impl Fn(usize, usize) -> usize,
+    ) -> Vec<&'a T>
+    where
+        T: PartialOrd,
+    {
+        setop_refs_by(|i| &self[ix1[i]], ix1.len(), |i| &v2[ix2[i]], ix2.len(), f)
+    }
+
+    /// General set operation on two slices sorted by their ascending sort indices, see `setop`.
+    /// Returns explicitly sorted result.
+    fn setop_indexed(
+        self,
+        ix1: &[usize],
+        v2: &[T],
+        ix2: &[usize],
+        f: impl Fn(usize, usize) -> usize,
+    ) -> Vec<T>
+    where
+        T: PartialOrd + Clone,
+    {
+        setop_by(|i| &self[ix1[i]], ix1.len(), |i| &v2[ix2[i]], ix2.len(), f)
+    }
+
+    /// Union of two index sorted slices, see `union`
+    fn union_indexed(self, ix1: &[usize], v2: &[T], ix2: &[usize], multi: bool) -> Vec<T>
+    where
+        T: PartialOrd + Clone,
+    {
+        self.setop_indexed(ix1, v2, ix2, union_rule(multi))
+    }
+
+    /// Intersection of two index sorted slices, see `intersection`
+    fn intersection_indexed(self, ix1: &[usize], v2: &[T], ix2: &[usize], multi: bool) -> Vec<T>
+    where
+        T: PartialOrd + Clone,
+    {
+        self.setop_indexed(ix1, v2, ix2, intersection_rule(multi))
+    }
+
+    /// Difference of two index sorted slices, see `difference`
+    fn difference_indexed(self, ix1: &[usize], v2: &[T], ix2: &[usize], multi: bool) -> Vec<T>
+    where
+        T: PartialOrd + Clone,
+    {
+        self.setop_indexed(ix1, v2, ix2, difference_rule(multi))
+    }
+
+    /// Symmetric difference of two index sorted slices, see `symdiff`
+    fn symdiff_indexed(self, ix1: &[usize], v2: &[T], ix2: &[usize], multi: bool) -> Vec<T>
+    where
+        T: PartialOrd + Clone,
+    {
+        self.setop_indexed(ix1, v2, ix2, symdiff_rule(multi))
+    }
+
+    /// Subset test of two index sorted slices, see `is_subset`
+    fn is_subset_indexed(self, ix1: &[usize], v2: &[T], ix2: &[usize], multi: bool) -> bool
+    where
+        T: PartialOrd,
+    {
+        subset_by(|i| &self[ix1[i]], ix1.len(), |i| &v2[ix2[i]], ix2.len(), multi)
+    }
+
+    /// Disjointness test of two index sorted slices, see `is_disjoint`
+    fn is_disjoint_indexed(self, ix1: &[usize], v2: &[T], ix2: &[usize]) -> bool
+    where
+        T: PartialOrd,
+    {
+        disjoint_by(|i| &self[ix1[i]], ix1.len(), |i| &v2[ix2[i]], ix2.len())
+    }
+
+    /// Jaccard similarity of two index sorted slices, see `jaccard`
+    fn jaccard_indexed(self, ix1: &[usize], v2: &[T], ix2: &[usize], multi: bool) -> f64
+    where
+        T: PartialOrd,
+    {
+        jaccard_by(|i| &self[ix1[i]], ix1.len(), |i| &v2[ix2[i]], ix2.len(), multi)
+    }
+
+    /// Sort-merge join of two ascending sorted key slices: self (the left table's keys)
+    /// and v2 (the right table's keys). Returns the pairs of row positions
+    /// `(left, right)` in ascending order of their keys, as selected by `kind` (see `Join`).
+    /// Duplicate keys are joined as a cross product of their rows.
+    /// Unmatched rows are paired with None, `Semi` and `Anti` give only the left rows.
+    fn join(self, v2: &[T], kind: Join) -> Vec<(Option<usize>, Option<usize>)>
+    where
+        T: PartialOrd,
+    {
+        join_by(|i| &self[i], |i| i, self.len(), |i| &v2[i], |i| i, v2.len(), kind)
+    }
+
+    /// Sort-merge join of two key slices, sorted via their ascending sort indices
+    /// (e.g. from `mergesort_indexed`), so that neither table need be reordered.
+    /// Returns the pairs of row positions in self and v2 (not in their indices), see `join`.
+    fn join_indexed(
+        self,
+        ix1: &[usize],
+        v2: &[T],
+        ix2: &[usize],
+        kind: Join,
+    ) -> Vec<(Option<usize>, Option<usize>)>
+    where
+        T: PartialOrd,
+    {
+        join_by(
+            |i| &self[ix1[i]],
+            |i| ix1[i],
+            ix1.len(),
+            |i| &v2[ix2[i]],
+            |i| ix2[i],
+            ix2.len(),
+            kind,
+        )
+    }
+
+    /// Intersection of any number of ascending sorted lists.
+    /// Returns each common item (once), together with the positions
+    /// of its first occurrences in all the lists (in the order of `lists`).
+    /// The candidates are taken from the shortest list, and are looked for in the others
+    /// by galloping (exponential followed by binary search) from their last positions.
+    /// Call as: `<&[T]>::intersect_many(&lists)`.
+    fn intersect_many(lists: &[&'a [T]]) -> Vec<(&'a T, Vec<usize>)>
+    where
+        T: PartialOrd,
+    {
+        let mut resvec: Vec<(&T, Vec<usize>)> = Vec::new();
+        if lists.is_empty() || lists.iter().any(|l| l.is_empty()) {
+            return resvec;
+        };
+        // list numbers, shortest list first
+        let mut order = <&[usize]>::newindex(lists.len());
+        order.sort_by_key(|&l| lists[l].len());
+        let shortest = lists[order[0]];
+        let mut cursors = vec![0_usize; lists.len()];
+        'candidates: while cursors[order[0]] < shortest.len() {
+            let c0 = cursors[order[0]];
+            let candidate = &shortest[c0];
+            cursors[order[0]] += run(&|i| &shortest[i], c0, shortest.len());
+            for &l in order.iter().skip(1) {
+                let list = lists[l];
+                let pos = gallop(|i| &list[i], cursors[l], list.len(), candidate);
+                cursors[l] = pos;
+                if pos == list.len() {
+                    break 'candidates; // this list is exhausted
+                };
+                if list[pos].partial_cmp(candidate) != Some(Equal) {
+                    continue 'candidates;
+                };
+            }
+            // candidate was found in all the lists
+            let mut positions = cursors.clone();
+            positions[order[0]] = c0;
+            resvec.push((candidate, positions));
+        }
+        resvec
+    }
+
+    /// Union of any number of ascending sorted lists.
+    /// Returns each distinct item once, in ascending order, together with
+    /// all its occurrences as (list number, position in the list) pairs.
+    /// Call as: `<&[T]>::unite_many(&lists)`.
+    fn unite_many(lists: &[&'a [T]]) -> Vec<(&'a T, Vec<(usize, usize)>)>
+    where
+        T: PartialOrd,
+    {
+        multiway(lists, |_| true)
+    }
+
+    /// Threshold ("at least m of n") intersection of ascending sorted lists.
+    /// Returns the items that occur in at least `m` different lists, together with
+    /// all their occurrences as (list number, position in the list) pairs.
+    /// With m = 1, this is the same as `unite_many`, with m = lists.len(),
+    /// it is an intersection.
+    /// Call as: `<&[T]>::threshold_many(&lists,m)`.
+    fn threshold_many(lists: &[&'a [T]], m: usize) -> Vec<(&'a T, Vec<(usize, usize)>)>
+    where
+        T: PartialOrd,
+    {
+        multiway(lists, |occurrences| {
+            // occurrences come grouped by their lists, count the different lists
+            let mut count = 0;
+            let mut last = usize::MAX;
+            for &(l, _) in occurrences {
+                if l != last {
+                    count += 1;
+                    last = l;
+                };
+            }
+            count >= m
+        })
+    }
+}
+
+/// Multiplicity rule of union
+fn union_rule(multi: bool) -> impl Fn(usize, usize) -> usize {
+    move |c1, c2| if multi { c1.max(c2) } else { 1 }
+}
+
+/// Multiplicity rule of intersection
+fn intersection_rule(multi: bool) -> impl Fn(usize, usize) -> usize {
+    move |c1, c2| {
+        if multi {
+            c1.min(c2)
+        } else {
+            usize::from(c1 > 0 && c2 > 0)
+        }
+    }
+}
+
+/// Multiplicity rule of difference
+fn difference_rule(multi: bool) -> impl Fn(usize, usize) -> usize {
+    move |c1, c2| {
+        if multi {
+            c1.saturating_sub(c2)
+        } else {
+            usize::from(c1 > 0 && c2 == 0)
+        }
+    }
+}
+
+/// Multiplicity rule of symmetric difference
+fn symdiff_rule(multi: bool) -> impl Fn(usize, usize) -> usize {
+    move |c1, c2| {
+        if multi {
+            c1.abs_diff(c2)
+        } else {
+            usize::from((c1 > 0) != (c2 > 0))
+        }
+    }
+}
+
+/// Number of consecutive items, starting at `start`, equal to the item at `start`
+fn run<'b, T>(at: &impl Fn(usize) -> &'b T, start: usize, len: usize) -> usize
+where
+    T: PartialOrd + 'b,
+{
+    let item = at(start);
+    let mut end = start + 1;
+    while end < len && at(end).partial_cmp(item) == Some(Equal) {
+        end += 1;
+    }
+    end - start
+}
+
+/// Walks two ascending sequences of lengths `l1` and `l2`, whose items are accessed by `a` and `b`.
+/// Calls `f(item, c1, c2)` once for every distinct item, with its multiplicities in both
+/// (the item is referenced in the first sequence, when it is present there).
+/// The walk terminates early when `f` returns false.
+pub(crate) fn counted_walk<'b, T>(
+    a: impl Fn(usize) -> &'b T,
+    l1: usize,
+    b: impl Fn(usize) -> &'b T,
+    l2: usize,
+    mut f: impl FnMut(&'b T, usize, usize) -> bool,
+) where
+    T: PartialOrd + 'b,
+{
+    let mut i1 = 0;
+    let mut i2 = 0;
+    while i1 < l1 || i2 < l2 {
+        // when one sequence is processed, the rest of the other is taken
+        let order = if i2 == l2 || (i1 < l1 && a(i1) < b(i2)) {
+            Less
+        } else if i1 == l1 || a(i1) > b(i2) {
+            Greater
+        } else {
+            Equal
+        };
+        let more = match order {
+            Less => {
+                let c1 = run(&a, i1, l1);
+                i1 += c1;
+                f(a(i1 - c1), c1, 0)
+            }
+            Greater => {
+                let c2 = run(&b, i2, l2);
+                i2 += c2;
+                f(b(i2 - c2), 0, c2)
+            }
+            Equal => {
+                let c1 = run(&a, i1, l1);
+                let c2 = run(&b, i2, l2);
+                i1 += c1;
+                i2 += c2;
+                f(a(i1 - c1), c1, c2)
+            }
+        };
+        if !more {
+            return;
+        };
+    }
+}
+
+/// Set operation with multiplicity rule `f` on two sorted sequences accessed by `a`, `b`.
+/// Returns references to their items.
+fn setop_refs_by<'b, T>(
+    a: impl Fn(usize) -> &'b T,
+    l1: usize,
+    b: impl Fn(usize) -> &'b T,
+    l2: usize,
+    f: impl Fn(usize, usize) -> usize,
+) -> Vec<&'b T>
+where
+    T: PartialOrd + 'b,
+{
+    let mut resvec: Vec<&T> = Vec::new();
+    counted_walk(a, l1, b, l2, |item, c1, c2| {
+        resvec.extend(std::iter::repeat_n(item, f(c1, c2)));
+        true
+    });
+    resvec
+}
+
+/// Set operation with multiplicity rule `f` on two sorted sequences accessed by `a`, `b`
+fn setop_by<'b, T>(
+    a: impl Fn(usize) -> &'b T,
+    l1: usize,
+    b: impl Fn(usize) -> &'b T,
+    l2: usize,
+    f: impl Fn(usize, usize) -> usize,
+) -> Vec<T>
+where
+    T: PartialOrd + Clone + 'b,
+{
+    setop_refs_by(a, l1, b, l2, f).into_iter().cloned().collect()
+}
+
+/// Join of two sorted sequences of keys accessed by `a`, `b`,
+/// whose row positions are given by `pa`, `pb`.
+/// Walks them by `counted_walk`, the sorted merge loop shared by all the set operations,
+/// rather than by the loop of `Vecops::merge_indices`: the join needs the whole runs of equal keys
+/// on both sides at once (to pair them as a cross product, or to tell that a key has no match),
+/// which `counted_walk` delivers as their counts, whereas `merge_indices` interleaves single items.
+fn join_by<'b, T>(
+    a: impl Fn(usize) -> &'b T,
+    pa: impl Fn(usize) -> usize,
+    l1: usize,
+    b: impl Fn(usize) -> &'b T,
+    pb: impl Fn(usize) -> usize,
+    l2: usize,
+    kind: Join,
+) -> Vec<(Option<usize>, Option<usize>)>
+where
+    T: PartialOrd + 'b,
+{
+    let mut pairs = Vec::new();
+    // start positions of the current runs of equal keys
+    let (mut i1, mut i2) = (0, 0);
+    counted_walk(a, l1, b, l2, |_, c1, c2| {
+        let (left, right) = (i1..i1 + c1, i2..i2 + c2);
+        match kind {
+            Join::Semi if c2 > 0 => pairs.extend(left.map(|i| (Some(pa(i)), None))),
+            Join::Anti if c2 == 0 => pairs.extend(left.map(|i| (Some(pa(i)), None))),
+            Join::Semi | Join::Anti => (),
+            _ if c1 > 0 && c2 > 0 => {
+                for i in left {
+                    pairs.extend(right.clone().map(|j| (Some(pa(i)), Some(pb(j)))));
+                }
+            }
+            Join::Left | Join::Full if c1 > 0 => pairs.extend(left.map(|i| (Some(pa(i)), None))),
+            Join::Right | Join::Full if c2 > 0 => pairs.extend(right.map(|j| (None, Some(pb(j))))),
+            _ => (),
+        };
+        i1 += c1;
+        i2 += c2;
+        true
+    });
+    pairs
+}
+
+/// Subset test on two sorted sequences accessed by `a`, `b`
+fn subset_by<'b, T>(
+    a: impl Fn(usize) -> &'b T,
+    l1: usize,
+    b: impl Fn(usize) -> &'b T,
+    l2: usize,
+    multi: bool,
+) -> bool
+where
+    T: PartialOrd + 'b,
+{
+    let mut subset = true;
+    counted_walk(a, l1, b, l2, |_, c1, c2| {
+        subset = if multi { c1 <= c2 } else { c1 == 0 || c2 > 0 };
+        subset
+    });
+    subset
+}
+
+/// Disjointness test on two sorted sequences accessed by `a`, `b`
+fn disjoint_by<'b, T>(
+    a: impl Fn(usize) -> &'b T,
+    l1: usize,
+    b: impl Fn(usize) -> &'b T,
+    l2: usize,
+) -> bool
+where
+    T: PartialOrd + 'b,
+{
+    let mut disjoint = true;
+    counted_walk(a, l1, b, l2, |_, c1, c2| {
+        disjoint = c1 == 0 || c2 == 0;
+        disjoint
+    });
+    disjoint
+}
+
+/// Jaccard similarity of two sorted sequences accessed by `a`, `b`
+fn jaccard_by<'b, T>(
+    a: impl Fn(usize) -> &'b T,
+    l1: usize,
+    b: impl Fn(usize) -> &'b T,
+    l2: usize,
+    multi: bool,
+) -> f64
+where
+    T: PartialOrd + 'b,
+{
+    let (mut inter, mut union) = (0_usize, 0_usize);
+    counted_walk(a, l1, b, l2, |_, c1, c2| {
+        if multi {
+            inter += c1.min(c2);
+            union += c1.max(c2);
+        } else {
+            inter += usize::from(c1 > 0 && c2 > 0);
+            union += 1;
+        };
+        true
+    });
+    if union == 0 {
+        return 1_f64;
+    };
+    inter as f64 / union as f64
+}
+
+/// Position of the first item not less than `target`, at or after `lo`,
+/// within an ascending sequence of length `len`, accessed by `at`.
+/// Gallops (doubles the step) to bracket the target, then uses `Search::binary_by`.
+pub(crate) fn gallop<'b, T>(at: impl Fn(usize) -> &'b T, lo: usize, len: usize, target: &T) -> usize
+where
+    T: PartialOrd + 'b,
+{
+    if lo >= len {
+        return len;
+    };
+    let mut step = 1;
+    let mut hi = lo;
+    while at(hi) < target {
+        if hi == len - 1 {
+            return len;
+        };
+        hi = (lo + step).min(len - 1);
+        step *= 2;
+    }
+    let start = lo + step / 4; // the previous probe, items up to it are all less
+    // Equal mapped to Greater, so the search always ends with the first item not less than target
+    match (start..=hi).binary_by(|probe| {
+        if at(probe) < target {
+            Less
+        } else {
+            Greater
+        }
+    }) {
+        Ok(pos) | Err(pos) => pos,
+    }
+}
+
+/// Multiway merge of ascending sorted lists, collecting all (list, position)
+/// occurrences of each distinct item. Items, whose occurrences are not accepted
+/// by `keep`, are left out.
+fn multiway<'b, T>(
+    lists: &[&'b [T]],
+    keep: impl Fn(&[(usize, usize)]) -> bool,
+) -> Vec<(&'b T, Vec<(usize, usize)>)>
+where
+    T: PartialOrd,
+{
+    let mut resvec: Vec<(&T, Vec<(usize, usize)>)> = Vec::new();
+    let mut cursors = vec![0_usize; lists.len()];
+    loop {
+        // find the smallest head item of all the lists
+        let mut head: Option<&T> = None;
+        for (list, &c) in lists.iter().zip(&cursors) {
+            if c < list.len() && head.is_none_or(|h| list[c] < *h) {
+                head = Some(&list[c]);
+            };
+        }
+        let Some(item) = head else {
+            break; // all lists are processed
+        };
+        let mut occurrences: Vec<(usize, usize)> = Vec::new();
+        for (l, list) in lists.iter().enumerate() {
+            while cursors[l] < list.len() && list[cursors[l]].partial_cmp(item) == Some(Equal) {
+                occurrences.push((l, cursors[l]));
+                cursors[l] += 1;
+            }
+        }
+        if occurrences.is_empty() {
+            // incomparable head item, skip it
+            for (l, list) in lists.iter().enumerate() {
+                if cursors[l] < list.len() && core::ptr::eq(&list[cursors[l]], item) {
+                    cursors[l] += 1;
+                };
+            }
+            continue;
+        };
+        if keep(&occurrences) {
+            resvec.push((item, occurrences));
+        };
+    }
+    resvec
+}
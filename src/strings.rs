@@ -0,0 +1,259 @@
+use crate::Indices;
+use core::{cmp::Ordering, cmp::Ordering::*, iter::Peekable, str::Chars};
+
+/// Buckets of up to this many strings are finished by comparison sort
+const RADIXSMALL: usize = 32;
+
+/// Stable MSD (most significant digit first) radix sort of strings, giving sort index.
+/// Strings are bucketed by one byte at a time, without any comparisons,
+/// except in small buckets, which are finished by comparison of their remaining suffixes.
+/// The result is in the plain (byte, that is Unicode code point) order of `Ord for str`.
+/// Works on `&[&str]`, `&[String]`, or any slice of `AsRef<str>`.
+pub fn radixsort_indexed<S: AsRef<str>>(v: &[S]) -> Vec<usize> {
+    let mut idx = <&[usize]>::newindex(v.len());
+    let mut buf = vec![0_usize; v.len()];
+    msd(v, &mut idx, &mut buf, 0);
+    idx
+}
+
+/// Sorted copy of strings (ascending or descending), by `radixsort_indexed`
+pub fn radixsort<S: AsRef<str> + Clone>(v: &[S], ascending: bool) -> Vec<S> {
+    radixsort_indexed(v).unindex(v, ascending)
+}
+
+/// Ranks of strings, obtained by inverting `radixsort_indexed`.
+/// When ascending is false, the ranks are complemented.
+pub fn radixrank<S: AsRef<str>>(v: &[S], ascending: bool) -> Vec<usize> {
+    let ranks = radixsort_indexed(v).invindex();
+    if ascending {
+        ranks
+    } else {
+        ranks.complindex()
+    }
+}
+
+/// Recursive body of `radixsort_indexed`: sorts `idx`, whose strings all share
+/// the same first `depth` bytes. `buf` is scratch space of the same length.
+fn msd<S: AsRef<str>>(v: &[S], idx: &mut [usize], buf: &mut [usize], depth: usize) {
+    if idx.len() <= RADIXSMALL {
+        idx.sort_by(|&a, &b| v[a].as_ref().as_bytes()[depth..].cmp(&v[b].as_ref().as_bytes()[depth..]));
+        return;
+    };
+    // bucket 0 is for the strings that end here, then one bucket per byte value
+    let key = |i: usize| v[i].as_ref().as_bytes().get(depth).map_or(0, |&b| b as usize + 1);
+    let mut starts = [0_usize; 258];
+    for &i in idx.iter() {
+        starts[key(i) + 1] += 1;
+    }
+    for b in 1..258 {
+        starts[b] += starts[b - 1];
+    }
+    let mut next = starts;
+    for &i in idx.iter() {
+        let k = key(i);
+        buf[next[k]] = i;
+        next[k] += 1;
+    }
+    idx.copy_from_slice(buf);
+    for b in 1..257 {
+        let (s, e) = (starts[b], starts[b + 1]);
+        if e - s > 1 {
+            msd(v, &mut idx[s..e], &mut buf[s..e], depth + 1);
+        };
+    }
+}
+
+/// Case-insensitive comparator of strings, for `isort_indexed`, `best_k`, etc.
+pub fn caseless<S: AsRef<str>>() -> impl Fn(&S, &S) -> Ordering + Copy {
+    collation(true, false, false)
+}
+
+/// Natural order comparator: digit runs compare by their numeric values,
+/// so that "file9" goes before "file10"
+pub fn natural<S: AsRef<str>>() -> impl Fn(&S, &S) -> Ordering + Copy {
+    collation(false, true, false)
+}
+
+/// Accent-folding comparator: accented Latin letters compare as their base letters
+pub fn accentless<S: AsRef<str>>() -> impl Fn(&S, &S) -> Ordering + Copy {
+    collation(false, false, true)
+}
+
+/// Collation comparator builder, combining any of the case-insensitive (`caseless`),
+/// natural numbers (`numeric`) and accent-folding (`accentless`) orders.
+/// Accent folding is locale independent: it uses a fixed table of the Latin-1 and Latin Extended-A letters,
+/// (e.g. 'é' as 'e', 'ß' as "ss", 'Æ' as "AE"). Strings equal under the collation are finally
+/// ordered by their plain order, so that the order is total and the sorts are repeatable.
+/// For searching, use `collation_eq` instead.
+pub fn collation<S: AsRef<str>>(
+    caseless: bool,
+    numeric: bool,
+    accentless: bool,
+) -> impl Fn(&S, &S) -> Ordering + Copy {
+    move |a: &S, b: &S| {
+        collation_eq(caseless, numeric, accentless)(a, b).then_with(|| a.as_ref().cmp(b.as_ref()))
+    }
+}
+
+/// Collation comparator without the final plain order tie-break of `collation`:
+/// strings that differ only in case, accents or leading zeros (as selected) compare `Equal`.
+/// For `binsearch_by` and `binsearch_indexed_by`, to find all such variants of the target
+/// in data sorted by `collation` (or by its ready made comparators) with the same arguments.
+pub fn collation_eq<S: AsRef<str>>(
+    caseless: bool,
+    numeric: bool,
+    accentless: bool,
+) -> impl Fn(&S, &S) -> Ordering + Copy {
+    move |a: &S, b: &S| {
+        cmp_keys(
+            KeyChars::new(a.as_ref(), caseless, accentless).peekable(),
+            KeyChars::new(b.as_ref(), caseless, accentless).peekable(),
+            numeric,
+        )
+    }
+}
+
+/// Compares two streams of collation key chars, with digit runs compared numerically when `numeric`
+fn cmp_keys(mut a: Peekable<KeyChars>, mut b: Peekable<KeyChars>, numeric: bool) -> Ordering {
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Equal,
+            (None, Some(_)) => return Less,
+            (Some(_), None) => return Greater,
+            (Some(x), Some(y)) if numeric && x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (na, nb) = (take_number(&mut a), take_number(&mut b));
+                // without leading zeros, longer numbers are greater
+                match na.len().cmp(&nb.len()).then_with(|| na.cmp(&nb)) {
+                    Equal => continue,
+                    ord => return ord,
+                }
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                };
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+/// Consumes a run of ascii digits, returning them without the leading zeros
+fn take_number(it: &mut Peekable<KeyChars>) -> String {
+    let mut digits = String::new();
+    while let Some(d) = it.next_if(|c| c.is_ascii_digit()) {
+        if !(digits.is_empty() && d == '0') {
+            digits.push(d);
+        };
+    }
+    digits
+}
+
+/// Iterator over the collation key chars of a string: folded and/or lower cased as required
+struct KeyChars<'s> {
+    chars: Chars<'s>,
+    caseless: bool,
+    accentless: bool,
+    pending: [char; 6],
+    npend: usize,
+    ipend: usize,
+}
+
+impl<'s> KeyChars<'s> {
+    fn new(s: &'s str, caseless: bool, accentless: bool) -> Self {
+        KeyChars {
+            chars: s.chars(),
+            caseless,
+            accentless,
+            pending: ['\0'; 6],
+            npend: 0,
+            ipend: 0,
+        }
+    }
+}
+
+impl Iterator for KeyChars<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        if self.ipend == self.npend {
+            // refill the pending chars from the next source char
+            let c = self.chars.next()?;
+            let mut utf8 = [0_u8; 4];
+            let src = match self.accentless.then(|| fold(c)).flatten() {
+                Some(folded) => folded,
+                None => c.encode_utf8(&mut utf8),
+            };
+            self.npend = 0;
+            self.ipend = 0;
+            for f in src.chars() {
+                if self.caseless {
+                    for l in f.to_lowercase() {
+                        self.pending[self.npend] = l;
+                        self.npend += 1;
+                    }
+                } else {
+                    self.pending[self.npend] = f;
+                    self.npend += 1;
+                };
+            }
+        };
+        self.ipend += 1;
+        Some(self.pending[self.ipend - 1])
+    }
+}
+
+/// Accent folding of the Latin-1 Supplement and Latin Extended-A letters to their base letters
+fn fold(c: char) -> Option<&'static str> {
+    Some(match c {
+        'À'..='Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'à'..='å' | 'ā' | 'ă' | 'ą' => "a",
+        'Æ' => "AE",
+        'æ' => "ae",
+        'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => "C",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'Ď' | 'Đ' | 'Ð' => "D",
+        'ď' | 'đ' | 'ð' => "d",
+        'È'..='Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'è'..='ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => "G",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'Ĥ' | 'Ħ' => "H",
+        'ĥ' | 'ħ' => "h",
+        'Ì'..='Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => "I",
+        'ì'..='ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'Ĳ' => "IJ",
+        'ĳ' => "ij",
+        'Ĵ' => "J",
+        'ĵ' => "j",
+        'Ķ' => "K",
+        'ķ' | 'ĸ' => "k",
+        'Ĺ' | 'Ļ' | 'Ľ' | 'Ŀ' | 'Ł' => "L",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'Ñ' | 'Ń' | 'Ņ' | 'Ň' | 'Ŋ' => "N",
+        'ñ' | 'ń' | 'ņ' | 'ň' | 'ŉ' | 'ŋ' => "n",
+        'Ò'..='Ö' | 'Ø' | 'Ō' | 'Ŏ' | 'Ő' => "O",
+        'ò'..='ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'Œ' => "OE",
+        'œ' => "oe",
+        'Ŕ' | 'Ŗ' | 'Ř' => "R",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'Ś' | 'Ŝ' | 'Ş' | 'Š' => "S",
+        'ś' | 'ŝ' | 'ş' | 'š' | 'ſ' => "s",
+        'ß' => "ss",
+        'Ţ' | 'Ť' | 'Ŧ' => "T",
+        'ţ' | 'ť' | 'ŧ' => "t",
+        'Þ' => "TH",
+        'þ' => "th",
+        'Ù'..='Ü' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => "U",
+        'ù'..='ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'Ŵ' => "W",
+        'ŵ' => "w",
+        'Ý' | 'Ŷ' | 'Ÿ' => "Y",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    })
+}
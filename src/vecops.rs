@@ -1,5 +1,10 @@
-use crate::{BinaryHeap, Indices, MinMax, Mutops, Search, Vecops};
+use crate::{
+    iters::{SortedWalk, WalkMode},
+    ranktree::Fenwick,
+    BinaryHeap, Indices, MinMax, Mutops, SortChoice, Search, Sortedness, Vecops,
+};
 use core::ops::Range;
+use std::collections::VecDeque;
 // use std::collections::binary_heap::PeekMut;
 use core::cmp::{Ordering, Ordering::*, Reverse};
 // use rayon::prelude::*;
@@ -21,6 +26,52 @@ impl<'a, T> Vecops<'a, T> for &'a [T] {
         self.iter().map(|x| f64::from(x.clone())).collect()
     }
 
+    /// Sample covariance (divided by n-1) of self and v.
+    /// Accumulated in one pass by Welford's updates of the means and co-moments,
+    /// which do not lose precision like the naive sums of products.
+    fn covariance(self, v: &[T]) -> f64
+    where
+        T: Clone,
+        f64: From<T>,
+    {
+        let m = comoments(self, v, None);
+        m.cxy / (m.w - 1.0)
+    }
+
+    /// Pearson's correlation coefficient of self and v, any numeric end type.
+    /// Accumulated in one pass by Welford's updates, see `covariance`.
+    /// Spearman's correlation, when applied to ranks.
+    fn correlation(self, v: &[T]) -> f64
+    where
+        T: Clone,
+        f64: From<T>,
+    {
+        comoments(self, v, None).correlation()
+    }
+
+    /// Weighted Pearson's correlation coefficient of self and v, with (non negative) weights `w`.
+    /// Accumulated in one pass by West's weighted version of Welford's updates.
+    fn wcorrelation(self, v: &[T], w: &[f64]) -> f64
+    where
+        T: Clone,
+        f64: From<T>,
+    {
+        comoments(self, v, Some(w)).correlation()
+    }
+
+    /// Partial correlation of self and y, controlling for the third variable z:
+    /// the correlation of what remains of them after their linear dependence on z is removed.
+    fn partial_correlation(self, y: &[T], z: &[T]) -> f64
+    where
+        T: Clone,
+        f64: From<T>,
+    {
+        let rxy = self.correlation(y);
+        let rxz = self.correlation(z);
+        let ryz = y.correlation(z);
+        (rxy - rxz * ryz) / ((1.0 - rxz * rxz) * (1.0 - ryz * ryz)).sqrt()
+    }
+
     /// Maximum value T of slice &[T]
     fn maxt(self) -> T
     where
@@ -120,6 +171,177 @@ impl<'a, T> Vecops<'a, T> for &'a [T] {
         }
     }
 
+    /// MinMax of every sliding window `self[j..j+w]`, in O(n) total time, instead of O(n*w)
+    /// by repeated `minmax_slice`. The result is aligned with the window end positions:
+    /// its item j is for the window ending at `j+w-1`, so there are `n-w+1` of them
+    /// (none when w > n). The indices are those of the first occurrences in self.
+    /// Candidate minima (and maxima) are kept in monotonic deques.
+    fn sliding_minmax(self, w: usize) -> Vec<MinMax<T>>
+    where
+        T: PartialOrd + Clone,
+    {
+        assert!(w > 0, "sliding_minmax: zero window");
+        let n = self.len();
+        let mut res = Vec::with_capacity((n + 1).saturating_sub(w));
+        let mut mins: VecDeque<usize> = VecDeque::with_capacity(w);
+        let mut maxs: VecDeque<usize> = VecDeque::with_capacity(w);
+        for (i, x) in self.iter().enumerate() {
+            // candidates that can no longer be the extremes are dropped
+            while mins.back().is_some_and(|&b| self[b] > *x) {
+                mins.pop_back();
+            }
+            mins.push_back(i);
+            while maxs.back().is_some_and(|&b| self[b] < *x) {
+                maxs.pop_back();
+            }
+            maxs.push_back(i);
+            if i + 1 < w {
+                continue;
+            };
+            // as are those that left the window
+            let start = i + 1 - w;
+            while mins.front().is_some_and(|&f| f < start) {
+                mins.pop_front();
+            }
+            while maxs.front().is_some_and(|&f| f < start) {
+                maxs.pop_front();
+            }
+            res.push(MinMax {
+                min: self[mins[0]].clone(),
+                minindex: mins[0],
+                max: self[maxs[0]].clone(),
+                maxindex: maxs[0],
+            });
+        }
+        res
+    }
+
+    /// Median of every sliding window `self[j..j+w]`, aligned with the window end positions
+    /// as in `sliding_minmax`. For even w, it is the mean of the two middle items,
+    /// as mapped to f64 by `quantify`. The items of the current window are kept
+    /// by their ranks in an order statistic (Fenwick) tree, so each step costs O(log n).
+    fn rolling_median(self, w: usize, quantify: impl Fn(&T) -> f64) -> Vec<f64>
+    where
+        T: PartialOrd + Clone,
+    {
+        assert!(w > 0, "rolling_median: zero window");
+        let n = self.len();
+        if w > n {
+            return Vec::new();
+        };
+        let idx = self.mergesort_indexed();
+        let ranks = idx.invindex();
+        let mut tree = Fenwick::new(n);
+        let mut res = Vec::with_capacity(n + 1 - w);
+        for i in 0..n {
+            tree.insert(ranks[i]);
+            if i >= w {
+                tree.remove(ranks[i - w]);
+            };
+            if i + 1 >= w {
+                let lo = &self[idx[tree.kth((w - 1) / 2)]];
+                let hi = &self[idx[tree.kth(w / 2)]];
+                res.push((quantify(lo) + quantify(hi)) / 2.0);
+            };
+        }
+        res
+    }
+
+    /// Rank (from 0) of the newest item of every sliding window `self[j..j+w]`,
+    /// among the items of its window: the number of items in the window lesser than it.
+    /// Aligned with the window end positions, as in `sliding_minmax`.
+    /// Uses an order statistic (Fenwick) tree, so each step costs O(log n).
+    fn rolling_rank(self, w: usize) -> Vec<usize>
+    where
+        T: PartialOrd + Clone,
+    {
+        assert!(w > 0, "rolling_rank: zero window");
+        let n = self.len();
+        if w > n {
+            return Vec::new();
+        };
+        let idx = self.mergesort_indexed();
+        let ranks = idx.invindex();
+        // the rank of the first of any equal items, so that ties are not counted as lesser
+        let mut lowranks = vec![0; n];
+        for p in 1..n {
+            lowranks[idx[p]] = if self[idx[p - 1]] < self[idx[p]] {
+                p
+            } else {
+                lowranks[idx[p - 1]]
+            };
+        }
+        let mut tree = Fenwick::new(n);
+        let mut res = Vec::with_capacity(n + 1 - w);
+        for i in 0..n {
+            tree.insert(ranks[i]);
+            if i >= w {
+                tree.remove(ranks[i - w]);
+            };
+            if i + 1 >= w {
+                res.push(tree.prefix(lowranks[i]));
+            };
+        }
+        res
+    }
+
+    /// Longest strictly increasing subsequence in the order of comparator `c`,
+    /// returned as the ascending subscripts of its items. O(n log n):
+    /// the smallest tails of the increasing subsequences of each length
+    /// are searched by `binary_by`. Pass in reversed comparator `c` for
+    /// the longest strictly decreasing subsequence. Applied to the ranks
+    /// of one ranking listed in the sort order of another, its length measures their agreement.
+    fn lis_indexed<F>(self, c: F) -> Vec<usize>
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        longest_run(self, c, true)
+    }
+
+    /// Longest non-decreasing subsequence in the order of comparator `c`,
+    /// returned as the ascending subscripts of its items, see `lis_indexed`.
+    fn lnds_indexed<F>(self, c: F) -> Vec<usize>
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        longest_run(self, c, false)
+    }
+
+    /// Patience sort: deals the items onto piles, each item onto the leftmost pile
+    /// whose top item is not greater than it, or onto a new pile.
+    /// Gives the minimal number of non-decreasing subsequences (piles of subscripts) covering self,
+    /// which equals the length of the longest strictly decreasing subsequence.
+    /// The piles can be merged to complete the sort.
+    fn patience_piles<F>(self, c: F) -> Vec<Vec<usize>>
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        let mut piles: Vec<Vec<usize>> = Vec::new();
+        for (i, x) in self.iter().enumerate() {
+            // the pile tops are in descending order, find the first one not greater than x
+            let pile = if piles.is_empty() {
+                0
+            } else {
+                match (0..=piles.len() - 1).binary_by(|p| {
+                    let top = &self[*piles[p].last().expect("patience_piles: empty pile")];
+                    if c(top, x) == Greater {
+                        Less
+                    } else {
+                        Greater
+                    }
+                }) {
+                    Ok(p) | Err(p) => p,
+                }
+            };
+            if pile == piles.len() {
+                piles.push(vec![i]);
+            } else {
+                piles[pile].push(i);
+            };
+        }
+        piles
+    }
+
     /// Using only a subset of self, defined by its idx subslice between i,i+n.
     /// Returns min of self, its index's index, max of self, its index's index.
     fn minmax_indexed(self, idx: &[usize], i: usize, n: usize) -> MinMax<T>
@@ -128,7 +350,7 @@ impl<'a, T> Vecops<'a, T> for &'a [T] {
     {
         let mut min = &self[idx[i]];
         let mut max = min;
-        let mut minix = 0; // indices of indices of min, max
+        let mut minix = i; // indices of indices of min, max
         let mut maxix = minix;
         for (ii, ix) in idx.iter().enumerate().skip(i + 1).take(n - 1) {
             if &self[*ix] < min {
@@ -221,6 +443,73 @@ impl<'a, T> Vecops<'a, T> for &'a [T] {
         count
     }
 
+    /// Run-length encoding of any slice.
+    /// Returns `(value, start, length)` for each run of consecutive equal items.
+    /// When self is sorted, these are all the groups of distinct values.
+    /// Decode by the function `vecops::decode_runs`.
+    fn group_runs(self) -> Vec<(T, usize, usize)>
+    where
+        T: PartialEq + Clone,
+    {
+        let mut runs: Vec<(T, usize, usize)> = Vec::new();
+        let mut start = 0;
+        for i in 1..=self.len() {
+            if i == self.len() || self[i] != self[start] {
+                runs.push((self[start].clone(), start, i - start));
+                start = i;
+            };
+        }
+        runs
+    }
+
+    /// Groups all the subscripts of each distinct value of self, using its sort index `idx`.
+    /// Groups come in the sort order of idx, the subscripts within each group
+    /// in their idx order (ascending when idx is from the stable `mergesort_indexed`).
+    fn group_by_index(self, idx: &[usize]) -> Vec<(T, Vec<usize>)>
+    where
+        T: PartialOrd + Clone,
+    {
+        let mut groups: Vec<(T, Vec<usize>)> = Vec::new();
+        let mut last: Option<&T> = None;
+        for &i in idx {
+            match last {
+                Some(val) if self[i].partial_cmp(val) == Some(Equal) => {
+                    groups
+                        .last_mut()
+                        .expect("group_by_index: None option (unexpected)")
+                        .1
+                        .push(i);
+                }
+                _ => {
+                    last = Some(&self[i]);
+                    groups.push((self[i].clone(), vec![i]));
+                }
+            }
+        }
+        groups
+    }
+
+    /// Distinct values of self and their counts.
+    /// Ascending by value, or, when `byfreq` is true, descending by count
+    /// (equal counts remain ascending by value).
+    fn value_counts(self, byfreq: bool) -> Vec<(T, usize)>
+    where
+        T: PartialOrd + Clone,
+    {
+        if self.is_empty() {
+            return vec![];
+        };
+        let mut counts: Vec<(T, usize)> = self
+            .group_by_index(&self.mergesort_indexed())
+            .into_iter()
+            .map(|(val, positions)| (val, positions.len()))
+            .collect();
+        if byfreq {
+            counts.sort_by_key(|&(_, count)| Reverse(count)); // stable
+        };
+        counts
+    }
+
     /// Unites (joins) two unsorted sets. For union of sorted sets, use `merge`
     fn unite_unsorted(self, v: &[T]) -> Vec<T>
     where
@@ -458,6 +747,175 @@ impl<'a, T> Vecops<'a, T> for &'a [T] {
         (negset, eqset, posset)
     }
 
+    /// Lazy merge of two ascending sorted slices.
+    /// Yields references to all their items in ascending order, like `merge`.
+    fn merge_iter(self, v2: &'a [T]) -> SortedWalk<'a, 'a, T>
+    where
+        T: PartialOrd,
+    {
+        SortedWalk::new(self, None, v2, None, WalkMode::Merge)
+    }
+
+    /// Lazy intersection of two ascending sorted slices, like `intersect`.
+    fn intersect_iter(self, v2: &'a [T]) -> SortedWalk<'a, 'a, T>
+    where
+        T: PartialOrd,
+    {
+        SortedWalk::new(self, None, v2, None, WalkMode::Intersect)
+    }
+
+    /// Lazy difference of two ascending sorted slices, like `diff`.
+    fn diff_iter(self, v2: &'a [T]) -> SortedWalk<'a, 'a, T>
+    where
+        T: PartialOrd,
+    {
+        SortedWalk::new(self, None, v2, None, WalkMode::Diff)
+    }
+
+    /// Lazy union of two index sorted slices, like `unite_indexed`.
+    fn unite_indexed_iter<'b>(self, ix1: &'b [usize], v2: &'a [T], ix2: &'b [usize]) -> SortedWalk<'a, 'b, T>
+    where
+        T: PartialOrd,
+    {
+        SortedWalk::new(self, Some(ix1), v2, Some(ix2), WalkMode::Unite)
+    }
+
+    /// Lazy intersection of two index sorted slices, like `intersect_indexed`.
+    fn intersect_indexed_iter<'b>(self, ix1: &'b [usize], v2: &'a [T], ix2: &'b [usize]) -> SortedWalk<'a, 'b, T>
+    where
+        T: PartialOrd,
+    {
+        SortedWalk::new(self, Some(ix1), v2, Some(ix2), WalkMode::Intersect)
+    }
+
+    /// Lazy difference of two index sorted slices, like `diff_indexed`.
+    fn diff_indexed_iter<'b>(self, ix1: &'b [usize], v2: &'a [T], ix2: &'b [usize]) -> SortedWalk<'a, 'b, T>
+    where
+        T: PartialOrd,
+    {
+        SortedWalk::new(self, Some(ix1), v2, Some(ix2), WalkMode::Diff)
+    }
+
+    /// Lazy partition with respect to a pivot.
+    /// Yields references to the items of self that compare as `which` to the pivot,
+    /// e.g. `Less` gives the first of the three sets of `partition`.
+    fn partition_iter<'b>(self, pivot: &'b T, which: Ordering) -> impl Iterator<Item = &'a T> + 'b
+    where
+        'a: 'b,
+        T: PartialOrd + 'a,
+    {
+        self.iter()
+            .filter(move |item| item.partial_cmp(&pivot) == Some(which))
+    }
+
+    /// Lazy partition with respect to a pivot.
+    /// Yields subscripts of the items of self that compare as `which` to the pivot.
+    fn partition_indexed_iter<'b>(self, pivot: &'b T, which: Ordering) -> impl Iterator<Item = usize> + 'b
+    where
+        'a: 'b,
+        T: PartialOrd,
+    {
+        self.iter()
+            .enumerate()
+            .filter(move |(_, item)| (*item).partial_cmp(pivot) == Some(which))
+            .map(|(i, _)| i)
+    }
+
+    /// References to the items of self in reverse order.
+    /// Zero-clone version of `revs`.
+    fn revs_refs(self) -> Vec<&'a T> {
+        self.iter().rev().collect()
+    }
+
+    /// References to the items of self, without the repetitions.
+    /// Zero-clone version of `sansrepeat`.
+    fn sansrepeat_refs(self) -> Vec<&'a T>
+    where
+        T: PartialEq,
+    {
+        let mut r: Vec<&T> = Vec::new();
+        for si in self {
+            if r.last().is_none_or(|&last| si != last) {
+                r.push(si)
+            };
+        }
+        r
+    }
+
+    /// Partition with respect to a pivot into three sets of references.
+    /// Zero-clone version of `partition`.
+    fn partition_refs(self, pivot: &T) -> (Vec<&'a T>, Vec<&'a T>, Vec<&'a T>)
+    where
+        T: PartialOrd,
+    {
+        let mut negset: Vec<&T> = Vec::new();
+        let mut eqset: Vec<&T> = Vec::new();
+        let mut posset: Vec<&T> = Vec::new();
+        for item in self {
+            match item.partial_cmp(pivot) {
+                Some(Less) => negset.push(item),
+                Some(Equal) => eqset.push(item),
+                Some(Greater) => posset.push(item),
+                None => continue,
+            };
+        }
+        (negset, eqset, posset)
+    }
+
+    /// References to the merged items of two ascending sorted slices.
+    /// Zero-clone version of `merge`.
+    fn merge_refs(self, v2: &'a [T]) -> Vec<&'a T>
+    where
+        T: PartialOrd,
+    {
+        self.merge_iter(v2).collect()
+    }
+
+    /// References to the intersection of two ascending sorted slices.
+    /// Zero-clone version of `intersect`.
+    fn intersect_refs(self, v2: &'a [T]) -> Vec<&'a T>
+    where
+        T: PartialOrd,
+    {
+        self.intersect_iter(v2).collect()
+    }
+
+    /// References to the difference of two ascending sorted slices.
+    /// Zero-clone version of `diff`.
+    fn diff_refs(self, v2: &'a [T]) -> Vec<&'a T>
+    where
+        T: PartialOrd,
+    {
+        self.diff_iter(v2).collect()
+    }
+
+    /// References to the union of two index sorted slices.
+    /// Zero-clone version of `unite_indexed`.
+    fn unite_indexed_refs(self, ix1: &[usize], v2: &'a [T], ix2: &[usize]) -> Vec<&'a T>
+    where
+        T: PartialOrd,
+    {
+        SortedWalk::new(self, Some(ix1), v2, Some(ix2), WalkMode::Unite).collect()
+    }
+
+    /// References to the intersection of two index sorted slices.
+    /// Zero-clone version of `intersect_indexed`.
+    fn intersect_indexed_refs(self, ix1: &[usize], v2: &'a [T], ix2: &[usize]) -> Vec<&'a T>
+    where
+        T: PartialOrd,
+    {
+        SortedWalk::new(self, Some(ix1), v2, Some(ix2), WalkMode::Intersect).collect()
+    }
+
+    /// References to the difference of two index sorted slices.
+    /// Zero-clone version of `diff_indexed`.
+    fn diff_indexed_refs(self, ix1: &[usize], v2: &'a [T], ix2: &[usize]) -> Vec<&'a T>
+    where
+        T: PartialOrd,
+    {
+        SortedWalk::new(self, Some(ix1), v2, Some(ix2), WalkMode::Diff).collect()
+    }
+
     /// Binary Search with automatic descending PartialOrder detection.
     fn binsearch(self, target: &T) -> Range<usize>
     where
@@ -478,6 +936,31 @@ impl<'a, T> Vecops<'a, T> for &'a [T] {
         }
     }
 
+    /// Binary Search of self, sorted in the order of comparator `c` (e.g. a `strings::collation`).
+    /// Returns the range of all the items matching `target`,
+    /// or the empty range at its insert position.
+    fn binsearch_by<F>(self, target: &T, c: F) -> Range<usize>
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        if self.is_empty() {
+            return 0..0;
+        };
+        (0..=self.len() - 1).binary_all(|probe| c(&self[probe], target))
+    }
+
+    /// Binary Search via sort index `idx` of self, sorted in the order of comparator `c`.
+    /// Returns the range of the matching positions in `idx`.
+    fn binsearch_indexed_by<F>(self, idx: &[usize], target: &T, c: F) -> Range<usize>
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        if idx.is_empty() {
+            return 0..0;
+        };
+        (0..=idx.len() - 1).binary_all(|probe| c(&self[idx[probe]], target))
+    }
+
     /// Binary Search via index with automatic PartialOrder detection.
     fn binsearch_indexed(self, idx: &[usize], target: &T) -> Range<usize>
     where
@@ -595,7 +1078,8 @@ impl<'a, T> Vecops<'a, T> for &'a [T] {
                 head2 = self[idx2[i2]].clone(); // else move to the next idx2 value
                 continue;
             }
-            // here the heads are equal, so consume both
+            // here the heads are equal, so consume only the first one,
+            // so that equal items keep their order (stable sort)
             residx.push(idx1[i1]);
             i1 += 1;
             if i1 == l1 {
@@ -604,14 +1088,6 @@ impl<'a, T> Vecops<'a, T> for &'a [T] {
                 break; // and terminate
             }
             head1 = self[idx1[i1]].clone();
-            residx.push(idx2[i2]);
-            i2 += 1;
-            if i2 == l2 {
-                // idx2 is now processed
-                idx1.iter().skip(i1).for_each(|&v| residx.push(v)); // copy out the rest of idx1
-                break; // and terminate
-            }
-            head2 = self[idx2[i2]].clone();
         }
         residx
     }
@@ -655,6 +1131,245 @@ impl<'a, T> Vecops<'a, T> for &'a [T] {
         self.mergesortslice(0, self.len())
     }
 
+    /// Sort index of self by the keys extracted by `key` from each item,
+    /// for slices of tuples or structs that are not themselves `PartialOrd`.
+    /// The key can be any `PartialOrd` type, e.g. a field, or a tuple of fields
+    /// compared lexicographically. Keys are extracted once per item (not per comparison)
+    /// and then sorted by the stable `mergesort_indexed`.
+    fn mergesort_indexed_by_key<K, F>(self, key: F) -> Vec<usize>
+    where
+        K: PartialOrd + Clone,
+        F: Fn(&T) -> K,
+    {
+        if self.is_empty() {
+            return Vec::new();
+        };
+        self.iter().map(key).collect::<Vec<K>>().mergesort_indexed()
+    }
+
+    /// Sort index of self by the f64 projections of its items by `key`,
+    /// extracted once per item and then sorted by `hashsort_indexed`.
+    fn hashsort_indexed_by_key<F>(self, key: F) -> Vec<usize>
+    where
+        F: Fn(&T) -> f64,
+    {
+        if self.is_empty() {
+            return Vec::new();
+        };
+        self.iter()
+            .map(key)
+            .collect::<Vec<f64>>()
+            .hashsort_indexed(|&x| x)
+    }
+
+    /// Ranks of the items of self by their keys, see `mergesort_indexed_by_key`.
+    /// Equal keys are ranked in their order of appearance (reversed when descending, as by `rank`).
+    fn rank_by_key<K, F>(self, key: F, ascending: bool) -> Vec<usize>
+    where
+        K: PartialOrd + Clone,
+        F: Fn(&T) -> K,
+    {
+        let ranks = self.mergesort_indexed_by_key(key).invindex();
+        if ascending {
+            ranks
+        } else {
+            ranks.complindex()
+        }
+    }
+
+    /// Binary Search for the `target` key, via sort index `idx` of self,
+    /// ascending by the keys extracted by `key` (as given by `mergesort_indexed_by_key`).
+    /// Returns the range of the matching positions in `idx`,
+    /// or the empty range at the target's insert position.
+    fn binsearch_indexed_by_key<K, F>(self, idx: &[usize], target: &K, key: F) -> Range<usize>
+    where
+        K: PartialOrd,
+        F: Fn(&T) -> K,
+    {
+        if idx.is_empty() {
+            return 0..0;
+        };
+        (0..=idx.len() - 1).binary_all(|probe| {
+            key(&self[idx[probe]])
+                .partial_cmp(target)
+                .expect("binsearch_indexed_by_key comparison failure")
+        })
+    }
+
+    /// Adaptive (natural) version of `mergesort_indexed`, in the manner of timsort.
+    /// Detects the existing non-descending runs and strictly descending runs (reversing the latter,
+    /// which keeps the sort stable). Runs shorter than 32 are extended and sorted by `mergesortslice`.
+    /// Adjacent runs are then merged pairwise, with galloping through long stretches
+    /// taken from the same run. Returns the same stable sort index as `mergesort_indexed`,
+    /// in O(n) time for sorted or reversed data and O(n log n) at worst.
+    fn mergesort_adaptive(self) -> Vec<usize>
+    where
+        T: PartialOrd + Clone,
+    {
+        const MINRUN: usize = 32;
+        let n = self.len();
+        let mut runs: Vec<Vec<usize>> = Vec::new();
+        let mut start = 0;
+        while start < n {
+            let mut end = start + 1;
+            if end < n && self[end] < self[start] {
+                while end < n && self[end] < self[end - 1] {
+                    end += 1;
+                }
+                if end - start >= MINRUN || end == n {
+                    runs.push((start..end).rev().collect());
+                    start = end;
+                    continue;
+                };
+            } else {
+                while end < n && self[end] >= self[end - 1] {
+                    end += 1;
+                }
+                if end - start >= MINRUN || end == n {
+                    runs.push((start..end).collect());
+                    start = end;
+                    continue;
+                };
+            };
+            // short run, extended and sorted as a whole
+            let len = MINRUN.min(n - start);
+            runs.push(self.mergesortslice(start, len));
+            start += len;
+        }
+        // merge adjacent pairs of runs, until only one remains
+        while runs.len() > 1 {
+            let mut merged = Vec::with_capacity(runs.len() / 2 + 1);
+            for pair in runs.chunks(2) {
+                match pair {
+                    [r1, r2] => merged.push(merge_runs(self, r1, r2)),
+                    _ => merged.push(pair[0].clone()),
+                }
+            }
+            runs = merged;
+        }
+        runs.pop().unwrap_or_default()
+    }
+
+    /// Quick measure of presortedness: the numbers of non-descending and descending runs (one pass)
+    /// and the fraction of inverted pairs, estimated from an evenly spaced sample of up to 128 items.
+    /// Returns `Sortedness`, whose `is_sorted()` tells if any sorting is needed at all.
+    fn sortedness(self) -> Sortedness
+    where
+        T: PartialOrd,
+    {
+        const SAMPLE: usize = 128;
+        let n = self.len();
+        if n < 2 {
+            return Sortedness {
+                len: n,
+                runs: n,
+                ..Default::default()
+            };
+        };
+        let mut runs = 1;
+        let mut descruns = 0;
+        let mut descending = false;
+        for i in 1..n {
+            if self[i] < self[i - 1] {
+                runs += 1;
+                if !descending {
+                    descruns += 1;
+                    descending = true;
+                };
+            } else {
+                descending = false;
+            };
+        }
+        let m = n.min(SAMPLE);
+        let sample: Vec<&T> = (0..m).map(|k| &self[k * n / m]).collect();
+        let mut inverted = 0_usize;
+        for q in 1..m {
+            inverted += sample[..q].iter().filter(|&&p| sample[q] < p).count();
+        }
+        Sortedness {
+            len: n,
+            runs,
+            descruns,
+            inversions: (2 * inverted) as f64 / (m * (m - 1)) as f64,
+        }
+    }
+
+    /// Chooses the sorting algorithm best suited to the data, see `SortChoice`.
+    /// Inspects the length, presortedness (`sortedness`), and the duplicate rate and
+    /// the spread of the `quantify`d values over the `minmax` range, both estimated from an evenly spaced sample.
+    /// Hash sort is chosen for long, well spread data, as it is then the fastest.
+    fn sort_choice(self, quantify: impl Copy + Fn(&T) -> f64) -> SortChoice
+    where
+        T: PartialOrd + Clone,
+    {
+        const SAMPLE: usize = 256;
+        const BUCKETS: usize = 16;
+        let n = self.len();
+        let sortedness = self.sortedness();
+        if sortedness.is_sorted() {
+            return SortChoice::Sorted;
+        };
+        if n <= 64 {
+            return SortChoice::Insert;
+        };
+        if sortedness.runs <= n / 32 || sortedness.inversions < 0.05 || sortedness.inversions > 0.95 {
+            return SortChoice::Adaptive;
+        };
+        let m = n.min(SAMPLE);
+        let sample: Vec<f64> = (0..m).map(|k| quantify(&self[k * n / m])).collect();
+        let mut sorted = sample.clone();
+        sorted.sort_unstable_by(|a, b| a.total_cmp(b));
+        sorted.dedup();
+        if 4 * sorted.len() < m {
+            // mostly repeated values
+            return SortChoice::Merge;
+        };
+        let minmax = self.minmax();
+        let (qmin, qmax) = (quantify(&minmax.min), quantify(&minmax.max));
+        if !(qmax - qmin).is_finite() {
+            return SortChoice::Merge;
+        };
+        let mut counts = [0_usize; BUCKETS];
+        for q in sample {
+            let b = ((BUCKETS as f64) * (q - qmin) / (qmax - qmin)) as usize;
+            counts[b.min(BUCKETS - 1)] += 1;
+        }
+        if 2 * counts.iter().max().unwrap_or(&0) > m {
+            // skewed distribution, hash buckets would be unbalanced
+            return SortChoice::Merge;
+        };
+        SortChoice::Hash
+    }
+
+    /// Sort index by the algorithm chosen by `sort_choice`, which is also returned for logging.
+    /// Equal items keep their data order, except when `SortChoice::Hash` is chosen.
+    fn smart_sort_indexed(self, quantify: impl Copy + Fn(&T) -> f64) -> (Vec<usize>, SortChoice)
+    where
+        T: PartialOrd + Clone,
+    {
+        let choice = self.sort_choice(quantify);
+        let idx = match choice {
+            SortChoice::Sorted => <&[usize]>::newindex(self.len()),
+            SortChoice::Insert => {
+                self.isort_indexed(0..self.len(), |a, b| a.partial_cmp(b).unwrap_or(Equal))
+            }
+            SortChoice::Adaptive => self.mergesort_adaptive(),
+            SortChoice::Merge => self.mergesort_indexed(),
+            SortChoice::Hash => self.hashsort_indexed(quantify),
+        };
+        (idx, choice)
+    }
+
+    /// Sorted data (ascending or descending) by the algorithm chosen by `sort_choice`,
+    /// which is also returned for logging.
+    fn smart_sort(self, quantify: impl Copy + Fn(&T) -> f64, ascending: bool) -> (Vec<T>, SortChoice)
+    where
+        T: PartialOrd + Clone,
+    {
+        let (idx, choice) = self.smart_sort_indexed(quantify);
+        (idx.unindex(self, ascending), choice)
+    }
+
     /// Immutable merge sort. Returns new sorted data vector (ascending or descending).
     /// Wraps mergesortslice.
     /// Mergesortslice and mergesort_indexed produce only an ascending index.
@@ -676,6 +1391,19 @@ impl<'a, T> Vecops<'a, T> for &'a [T] {
         }
     }
 
+    /// References to self in ascending or descending sort order.
+    /// Zero-clone version of `sortm`: a stable index sort that only compares
+    /// the referenced items, so `T` need not be `Clone`.
+    /// Equal items keep their data order, as in `sortm`.
+    fn sortm_refs(self, ascending: bool) -> Vec<&'a T>
+    where
+        T: PartialOrd,
+    {
+        let mut idx = <&[usize]>::newindex(self.len());
+        idx.sort_by(|&a, &b| self[a].partial_cmp(&self[b]).unwrap_or(Equal));
+        idx.unindex_refs(self, ascending)
+    }
+
     /// Fast ranking of many T items, with only `n*(log(n)+1)` complexity.
     /// Ranking is done by inverting the sort index.
     /// Sort index is in sorted PartialOrder, giving data positions.
@@ -702,6 +1430,97 @@ impl<'a, T> Vecops<'a, T> for &'a [T] {
         rankvec
     }
 
+    /// Inserts the subscripts of items newly pushed onto the end of self into
+    /// existing ascending sort index `idx`, without re-sorting everything.
+    /// A single new item is placed by binary insertion, a batch of them
+    /// is first sorted on its own and then merged in by `merge_indices`.
+    /// Equal items stay in their data order, as produced by `mergesort_indexed`.
+    /// When `ranks` (obtained by `idx.invindex()`) are given, they are updated too.
+    fn append_indexed(self, idx: &mut Vec<usize>, ranks: Option<&mut Vec<usize>>)
+    where
+        T: PartialOrd + Clone,
+    {
+        let n0 = idx.len();
+        assert!(n0 <= self.len(), "append_indexed: idx is longer than data");
+        match self.len() - n0 {
+            0 => (), // nothing new
+            1 => {
+                let sortpos = insert_pos(self, n0, |p| idx[p], n0);
+                idx.insert(sortpos, n0);
+                if let Some(r) = ranks {
+                    r.iter_mut().filter(|x| **x >= sortpos).for_each(|x| *x += 1);
+                    r.push(sortpos);
+                };
+            }
+            k => {
+                let newidx = self.mergesortslice(n0, k);
+                *idx = if n0 == 0 {
+                    newidx
+                } else {
+                    self.merge_indices(idx, &newidx)
+                };
+                if let Some(r) = ranks {
+                    *r = idx.invindex();
+                };
+            }
+        }
+    }
+
+    /// Moves subscript `pos` within sort index `idx` to its new sort position,
+    /// after the value of `self[pos]` has been changed.
+    /// Only the part of `idx` (and of `ranks`, when given) between the old
+    /// and the new sort positions is shifted.
+    fn update_indexed(self, idx: &mut [usize], pos: usize, ranks: Option<&mut Vec<usize>>)
+    where
+        T: PartialOrd,
+    {
+        let n = idx.len();
+        let oldpos = match ranks {
+            Some(ref r) => r[pos],
+            None => idx
+                .iter()
+                .position(|&i| i == pos)
+                .expect("update_indexed: pos is not in idx"),
+        };
+        // search the index as if `pos` was already taken out of it
+        let newpos = insert_pos(
+            self,
+            n - 1,
+            |p| if p < oldpos { idx[p] } else { idx[p + 1] },
+            pos,
+        );
+        let (lo, hi) = if newpos < oldpos {
+            idx[newpos..=oldpos].rotate_right(1);
+            (newpos, oldpos)
+        } else {
+            idx[oldpos..=newpos].rotate_left(1);
+            (oldpos, newpos)
+        };
+        if let Some(r) = ranks {
+            for (p, &i) in idx.iter().enumerate().take(hi + 1).skip(lo) {
+                r[i] = p
+            }
+        };
+    }
+
+    /// Deletes subscript `pos` from sort index `idx` after the item `pos`
+    /// has been removed from the data (e.g. by `Vec::remove(pos)`),
+    /// so self is the data already without it.
+    /// Subscripts of the following items are moved down by one.
+    fn remove_indexed(self, idx: &mut Vec<usize>, pos: usize, ranks: Option<&mut Vec<usize>>) {
+        let sortpos = idx
+            .iter()
+            .position(|&i| i == pos)
+            .expect("remove_indexed: pos is not in idx");
+        idx.remove(sortpos);
+        idx.iter_mut().filter(|i| **i > pos).for_each(|i| *i -= 1);
+        if let Some(r) = ranks {
+            r.remove(pos);
+            r.iter_mut().filter(|x| **x > sortpos).for_each(|x| *x -= 1);
+        };
+        debug_assert_eq!(idx.len(), self.len());
+    }
+
     /// swap any two index items, if their data items (self) are not in ascending PartialOrder
     fn isorttwo(self, idx: &mut [usize], i0: usize, i1: usize)
     where
@@ -731,10 +1550,27 @@ impl<'a, T> Vecops<'a, T> for &'a [T] {
         T: PartialOrd + Clone,
     {
         let n = self.len();
-        let (min, max) = self.minmaxt();
+        let Some((min, max)) = hashrange(self, quantify) else {
+            return self.hashsort_robust_indexed(quantify);
+        };
         // create a mutable index for the result
         let mut idx = Vec::from_iter(0..n);
-        self.hashsortslice(&mut idx, 0, n, quantify(&min), quantify(&max), quantify); // sorts idx
+        self.hashsortslice(&mut idx, 0, n, min, max, quantify); // sorts idx
+        idx
+    }
+
+    /// Hardened version of `hashsort_indexed`, for skewed, clustered or infinite data.
+    /// Bucket boundaries are quantiles of an evenly spaced sample of the `quantify`d values
+    /// (equi-depth buckets), so that a few outliers can not starve the other buckets.
+    /// Items quantified to -inf go first, +inf and NaN last.
+    /// Recursion is limited in depth, after which (or when a bucket does not split any further)
+    /// a comparison sort takes over. The sort is stable.
+    fn hashsort_robust_indexed(self, quantify: impl Copy + Fn(&T) -> f64) -> Vec<usize>
+    where
+        T: PartialOrd,
+    {
+        let mut idx = Vec::from_iter(0..self.len());
+        robust_hashsort(self, &mut idx, quantify, HASHDEPTH);
         idx
     }
 
@@ -815,7 +1651,9 @@ impl<'a, T> Vecops<'a, T> for &'a [T] {
                     if mx.min < mx.max {
                         // recurse with the new range
                         self.isorttwo(idx, isub, mx.minindex); // swap minindex to the front
-                        self.isorttwo(idx, mx.maxindex, isub + n - 1); // swap maxindex to the end
+                        // the max may have just been swapped away from the front
+                        let maxindex = if mx.maxindex == isub { mx.minindex } else { mx.maxindex };
+                        self.isorttwo(idx, maxindex, isub + n - 1); // swap maxindex to the end
                                                                        // recurse to sort the rest
                         self.hashsortslice(
                             idx,
@@ -839,7 +1677,9 @@ impl<'a, T> Vecops<'a, T> for &'a [T] {
                     if mx.min < mx.max {
                         // else are all equal
                         self.isorttwo(idx, isubprev, mx.minindex); // swap minindex to the front
-                        self.isorttwo(idx, mx.maxindex, isub - 1); // swap maxindex to the end
+                        // the max may have just been swapped away from the front
+                        let maxindex = if mx.maxindex == isubprev { mx.minindex } else { mx.maxindex };
+                        self.isorttwo(idx, maxindex, isub - 1); // swap maxindex to the end
                         self.hashsortslice(
                             idx,
                             isubprev + 1,
@@ -1051,7 +1891,7 @@ impl<'a, T> Vecops<'a, T> for &'a [T] {
         let mut k_sorted: Vec<&T> = self.iter().skip(rng.start).take(k).collect();
         k_sorted.sort_unstable_by(|&a, &b| c(a, b));
         let mut k_max = k_sorted[k - 1];
-        for s in self.iter().take(n - k).skip(rng.start + k) {
+        for s in self.iter().take(rng.end).skip(rng.start + k) {
             if c(s, k_max) == Less {
                 let insert_pos = match k_sorted.binary_search_by(|j| c(j, s)) {
                     Ok(ins) => ins + 1,
@@ -1125,3 +1965,264 @@ impl<'a, T> Vecops<'a, T> for &'a [T] {
         idx
     }
 }
+
+/// Decodes the runs of `Vecops::group_runs` back into data (inverse of `group_runs`)
+pub fn decode_runs<T>(runs: &[(T, usize, usize)]) -> Vec<T>
+where
+    T: Clone,
+{
+    let mut res = Vec::with_capacity(runs.last().map_or(0, |&(_, start, len)| start + len));
+    for (val, _, len) in runs {
+        res.extend(std::iter::repeat_n(val, *len).cloned())
+    }
+    res
+}
+
+/// Sort position for data item `v[pos]` within an ascending sort index of length `n`,
+/// whose subscripts are probed by `at`. Found by `Search::binary_by`.
+/// Equal items are ordered by their subscripts, so the sort remains stable.
+fn insert_pos<T>(v: &[T], n: usize, at: impl Fn(usize) -> usize, pos: usize) -> usize
+where
+    T: PartialOrd,
+{
+    if n == 0 {
+        return 0;
+    };
+    match (0..=n - 1).binary_by(|probe| {
+        let i = at(probe);
+        v[i].partial_cmp(&v[pos])
+            .expect("insert_pos comparison failure")
+            .then(i.cmp(&pos))
+    }) {
+        Ok(sortpos) | Err(sortpos) => sortpos,
+    }
+}
+
+/// Stable merge of two adjacent ascending runs of sort index subscripts, `r1` preceding `r2`.
+/// After either run wins `MINGALLOP` times in a row, all its following items
+/// that go before the head of the other run are found by galloping and copied in one go.
+fn merge_runs<T>(v: &[T], r1: &[usize], r2: &[usize]) -> Vec<usize>
+where
+    T: PartialOrd,
+{
+    const MINGALLOP: usize = 7;
+    let mut res = Vec::with_capacity(r1.len() + r2.len());
+    let (mut i1, mut i2) = (0, 0);
+    let (mut wins1, mut wins2) = (0, 0);
+    while i1 < r1.len() && i2 < r2.len() {
+        if wins1 >= MINGALLOP {
+            // items of r1 not greater than the head of r2 come first
+            let end = gallop_run(r1, i1, |k| v[k] <= v[r2[i2]]);
+            res.extend_from_slice(&r1[i1..end]);
+            i1 = end;
+            wins1 = 0;
+            continue;
+        };
+        if wins2 >= MINGALLOP {
+            // items of r2 lesser than the head of r1 come first
+            let end = gallop_run(r2, i2, |k| v[k] < v[r1[i1]]);
+            res.extend_from_slice(&r2[i2..end]);
+            i2 = end;
+            wins2 = 0;
+            continue;
+        };
+        if v[r2[i2]] < v[r1[i1]] {
+            res.push(r2[i2]);
+            i2 += 1;
+            wins2 += 1;
+            wins1 = 0;
+        } else {
+            res.push(r1[i1]);
+            i1 += 1;
+            wins1 += 1;
+            wins2 = 0;
+        };
+    }
+    res.extend_from_slice(&r1[i1..]);
+    res.extend_from_slice(&r2[i2..]);
+    res
+}
+
+/// End of the prefix of `run` from `lo`, whose subscripts all satisfy `pred`.
+/// Exponential probing followed by binary search.
+fn gallop_run(run: &[usize], lo: usize, pred: impl Fn(usize) -> bool) -> usize {
+    let mut bound = lo; // pred holds for all below bound
+    let mut probe = lo;
+    let mut step = 1;
+    while probe < run.len() && pred(run[probe]) {
+        bound = probe + 1;
+        probe += step;
+        step *= 2;
+    }
+    let hi = probe.min(run.len());
+    bound + run[bound..hi].partition_point(|&k| pred(k))
+}
+
+/// Quantified (min, max) of non-empty `v`, for the hash sorts.
+/// None when the range is not finite, as an infinite range would break the hashing:
+/// `hashsort_indexed` and `muthashsort` then fall back to `robust_hashsort`.
+pub(crate) fn hashrange<T>(v: &[T], quantify: impl Fn(&T) -> f64) -> Option<(f64, f64)>
+where
+    T: PartialOrd + Clone,
+{
+    let (min, max) = v.minmaxt();
+    let (qmin, qmax) = (quantify(&min), quantify(&max));
+    (qmax - qmin).is_finite().then_some((qmin, qmax))
+}
+
+/// Recursion depth limit of `hashsort_robust_indexed`, beyond which comparison sort takes over
+pub(crate) const HASHDEPTH: usize = 8;
+
+/// Stable, quantile bucketed hash sort of `idx`, whose subscripts (into `v`) are given in data order.
+/// Used by `hashsort_robust_indexed` and `muthashsort_stable`.
+pub(crate) fn robust_hashsort<T>(
+    v: &[T],
+    idx: &mut [usize],
+    quantify: impl Copy + Fn(&T) -> f64,
+    depth: usize,
+) where
+    T: PartialOrd,
+{
+    let n = idx.len();
+    if n <= 32 || depth == 0 {
+        // std stable sort, as a comparison sort fallback
+        idx.sort_by(|&a, &b| v[a].partial_cmp(&v[b]).unwrap_or(Equal));
+        return;
+    };
+    let k = (n / 16).clamp(2, 1024); // number of finite buckets
+    let step = (n / (4 * k)).max(1);
+    let mut sample: Vec<f64> = idx
+        .iter()
+        .step_by(step)
+        .map(|&i| quantify(&v[i]))
+        .filter(|q| q.is_finite())
+        .collect();
+    sample.sort_unstable_by(|a, b| a.total_cmp(b));
+    let mut splitters: Vec<f64> = (1..k)
+        .filter_map(|j| sample.get(j * sample.len() / k).copied())
+        .collect();
+    splitters.dedup(); // clustered values give fewer buckets
+    // bucket 0 holds -inf, then the finite buckets, the last one holds +inf and NaN
+    let nb = splitters.len() + 3;
+    let mut buckets: Vec<Vec<usize>> = vec![Vec::new(); nb];
+    for &i in idx.iter() {
+        let q = quantify(&v[i]);
+        let b = if q == f64::NEG_INFINITY {
+            0
+        } else if q.is_finite() {
+            1 + splitters.partition_point(|&s| s <= q)
+        } else {
+            nb - 1
+        };
+        buckets[b].push(i);
+    }
+    let mut isub = 0;
+    for bucket in buckets {
+        let blen = bucket.len();
+        idx[isub..isub + blen].copy_from_slice(&bucket);
+        if blen > 1 {
+            // a bucket that did not split the items any further goes straight to comparison sort
+            let d = if blen == n { 0 } else { depth - 1 };
+            robust_hashsort(v, &mut idx[isub..isub + blen], quantify, d);
+        };
+        isub += blen;
+    }
+}
+
+/// Running (weighted) means and co-moments of paired values,
+/// updated by Welford's (West's, when weighted) numerically stable algorithm
+#[derive(Default)]
+pub(crate) struct Comoments {
+    /// Sum of the weights (the count, when unweighted)
+    w: f64,
+    mx: f64,
+    my: f64,
+    cxx: f64,
+    cyy: f64,
+    cxy: f64,
+}
+
+impl Comoments {
+    /// Adds the pair (x,y) with weight wi
+    pub(crate) fn push(&mut self, x: f64, y: f64, wi: f64) {
+        self.w += wi;
+        if self.w == 0.0 {
+            return;
+        };
+        let dx = x - self.mx;
+        let dy = y - self.my;
+        self.mx += wi / self.w * dx;
+        self.my += wi / self.w * dy;
+        self.cxx += wi * dx * (x - self.mx);
+        self.cyy += wi * dy * (y - self.my);
+        self.cxy += wi * dx * (y - self.my);
+    }
+
+    /// Pearson's correlation of the pairs pushed so far
+    pub(crate) fn correlation(&self) -> f64 {
+        self.cxy / (self.cxx * self.cyy).sqrt()
+    }
+}
+
+/// Co-moments of x and y, optionally weighted by w
+fn comoments<T>(x: &[T], y: &[T], w: Option<&[f64]>) -> Comoments
+where
+    T: Clone,
+    f64: From<T>,
+{
+    assert_eq!(x.len(), y.len(), "comoments: data lengths differ");
+    let mut m = Comoments::default();
+    match w {
+        Some(w) => {
+            assert_eq!(x.len(), w.len(), "comoments: weights length differs");
+            for ((xi, yi), &wi) in x.iter().zip(y).zip(w) {
+                m.push(f64::from(xi.clone()), f64::from(yi.clone()), wi);
+            }
+        }
+        None => {
+            for (xi, yi) in x.iter().zip(y) {
+                m.push(f64::from(xi.clone()), f64::from(yi.clone()), 1.0);
+            }
+        }
+    }
+    m
+}
+
+/// Longest strictly increasing (when `strict`) or non-decreasing subsequence of `v`,
+/// as its subscripts. Used by `lis_indexed` and `lnds_indexed`.
+fn longest_run<T, F>(v: &[T], c: F, strict: bool) -> Vec<usize>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    // tails[k] is the subscript of the smallest tail item of the subsequences of length k+1
+    let mut tails: Vec<usize> = Vec::new();
+    let mut prev: Vec<Option<usize>> = vec![None; v.len()];
+    for (i, x) in v.iter().enumerate() {
+        // the first tail that x can replace: not lesser than x when strict, greater than x otherwise
+        let k = if tails.is_empty() {
+            0
+        } else {
+            match (0..=tails.len() - 1).binary_by(|k| match c(&v[tails[k]], x) {
+                Less => Less,
+                Equal if !strict => Less,
+                _ => Greater,
+            }) {
+                Ok(k) | Err(k) => k,
+            }
+        };
+        prev[i] = k.checked_sub(1).map(|p| tails[p]);
+        if k == tails.len() {
+            tails.push(i);
+        } else {
+            tails[k] = i;
+        };
+    }
+    let mut res = Vec::with_capacity(tails.len());
+    let mut link = tails.last().copied();
+    while let Some(i) = link {
+        res.push(i);
+        link = prev[i];
+    }
+    res.reverse();
+    res
+}
@@ -2,7 +2,10 @@
 #![allow(dead_code)]
 #[cfg(test)]
 use core::{cmp::{Ordering::*, Reverse},convert::identity};
-use indxvec::{here, printing::*, qsortf64, Indices, Mutops, Printing, Search, Vecops};
+use indxvec::{
+    here, minmax::MinMaxStats, multikey::*, printing::*, random::*, ranktree::RankTree, rmq::*, strings::*, vecops::decode_runs, qsortf64, Axis, Correlation, Indices, Join, Matrix, Mutops, Pivot, Printing, Search, Setops, SortChoice, Sortedness,
+    Vecops,
+};
 use ran::*;
 use std::{cmp::Ord, convert::From};
 use times::*;
@@ -292,6 +295,45 @@ fn text() {
 
 use core::ops::Range;
 
+#[test]
+fn stability() {
+    // equal items must keep their data order
+    let v = [5_u8, 5, 1, 5, 0];
+    assert_eq!(v.mergesort_indexed(), [4, 2, 0, 1, 3]);
+    assert_eq!(v.rank(true), [2, 3, 1, 4, 0]);
+    let w = [1_u8, 1, 1, 0];
+    assert_eq!(w.mergesort_indexed(), [3, 0, 1, 2]);
+    let (merged, idx) = w.merge_indexed(&w.mergesort_indexed(), &v, &v.mergesort_indexed());
+    println!("Merged: {} index: {}", merged.gr(), idx.gr());
+    assert_eq!(idx, [3, 8, 0, 1, 2, 6, 4, 5, 7]);
+}
+
+#[test]
+fn hashsortfixes() {
+    // positions are subscripts of idx, also when the subslice starts past 0
+    let v = [5_u8, 1, 9, 3];
+    let mm = v.minmax_indexed(&[0, 1, 2, 3], 2, 2);
+    assert_eq!((mm.minindex, mm.maxindex), (3, 2));
+    // the maximum found at the front of a bucket must not be lost by the min swap
+    for seed in 0..200 {
+        set_seeds(seed + 1);
+        let d = ranv_u8(40).expect("ranv_u8 failed");
+        let sorted = d.hashsort_indexed(|&x| x as f64).unindex(&d, true);
+        assert!(sorted.windows(2).all(|w| w[0] <= w[1]), "seed {seed}: {}", sorted.gr());
+    }
+}
+
+#[test]
+fn best_k_tail() {
+    // the best items are among the last k of the range
+    let v = [9_u8, 8, 7, 6, 5, 4, 1, 0];
+    let best = v.best_k(3, 0..8, |a, b| a.cmp(b));
+    println!("Best 3: {}", best.gr());
+    assert_eq!(best, [&0, &1, &4]);
+    assert_eq!(v.best_k(2, 2..7, |a, b| a.cmp(b)), [&1, &4]);
+    assert_eq!(v.best_k(2, 2..7, |a, b| a.cmp(b)), v.best_k_indexed(2, 2..7, |a, b| a.cmp(b)).iter().map(|&i| &v[i]).collect::<Vec<&u8>>());
+}
+
 #[test]
 fn solvetest() {
     let num: f64 = 1234567890.0;
@@ -414,6 +456,588 @@ fn sorts() {
     mutbenchu8(10..5011, 1000, 10, &NAMES, &closures);
 }
 
+#[test]
+fn introsort() {
+    // non numeric, non Copy keys
+    let mut words: Vec<String> = "the quick brown fox jumps over the lazy dog and the cat"
+        .split(' ').map(String::from).collect();
+    words.mutsort_by(|a, b| b.cmp(a));
+    println!("Descending words by mutsort_by:\n{}", words.gr());
+    assert!(words.windows(2).all(|w| w[0] >= w[1]));
+    // many repeats, presorted and reversed data
+    set_seeds(7);
+    let rv = ranv_u8(20000).expect("ranv_u8 failed");
+    let mut sorted = rv.clone();
+    sorted.sort_unstable();
+    for mut v in [rv.clone(), sorted.clone(), sorted.iter().rev().copied().collect::<Vec<u8>>()] {
+        v.mutsort_by(|a, b| a.cmp(b));
+        assert_eq!(v, sorted);
+    }
+    const NAMES: [&str; 4] = ["sortm", "muthashsort", "mutsort_by", "sort_unstable"];
+    let closures = [
+        |v: &mut [u8]| {
+            v.sortm(true);
+        },
+        |v: &mut [u8]| {
+            v.muthashsort(|&t| t as f64);
+        },
+        |v: &mut [u8]| {
+            v.mutsort_by(|a, b| a.cmp(b));
+        },
+        |v: &mut [u8]| {
+            v.sort_unstable();
+        },
+    ];
+    set_seeds(0_u64);
+    mutbenchu8(10..5011, 1000, 10, &NAMES, &closures);
+}
+
+#[test]
+fn adaptive() {
+    set_seeds(3);
+    let rv = ranv_u8(1000).expect("ranv_u8 failed");
+    let mut sorted = rv.sortm(true);
+    // nearly sorted data: a few disordered items, then a descending tail
+    sorted.swap(100, 700);
+    sorted.extend((0..200_u8).rev());
+    let nearly: Sortedness = sorted.sortedness();
+    println!("Nearly sorted: {nearly}");
+    println!("Random:        {}", rv.sortedness());
+    assert!(!nearly.is_sorted());
+    assert!(nearly.inversions < rv.sortedness().inversions);
+    for v in [&rv, &sorted] {
+        let idx = v.mergesort_adaptive();
+        assert_eq!(idx, v.mergesort_indexed()); // same stable sort index
+        assert!(idx.unindex(v, true).sortedness().is_sorted());
+    }
+    let reversed: Vec<u8> = (0..=255_u8).rev().collect();
+    assert_eq!(reversed.sortedness().runs, 256);
+    assert_eq!(reversed.sortedness().inversions, 1.0);
+    const NAMES: [&str; 2] = ["mergesort_indexed", "mergesort_adaptive"];
+    let closures = [
+        |v: &mut [u8]| {
+            v.sort_unstable();
+            let half = v.len() / 2;
+            v[half..].reverse();
+            v.mergesort_indexed();
+        },
+        |v: &mut [u8]| {
+            v.sort_unstable();
+            let half = v.len() / 2;
+            v[half..].reverse();
+            v.mergesort_adaptive();
+        },
+    ];
+    set_seeds(0_u64);
+    mutbenchu8(10..5011, 1000, 10, &NAMES, &closures);
+}
+
+#[test]
+fn smartsort() {
+    set_seeds(5);
+    let rf = ranv_f64(5000).expect("ranv_f64 failed");
+    let (sorted, choice) = rf.smart_sort(|&t| t, true);
+    println!("Random f64s sorted by {choice}");
+    assert_eq!(choice, SortChoice::Hash);
+    assert!(sorted.sortedness().is_sorted());
+    let (_, choice) = sorted.smart_sort_indexed(|&t| t);
+    println!("Sorted f64s: {choice}");
+    assert_eq!(choice, SortChoice::Sorted);
+    let mut nearly = sorted.clone();
+    nearly.swap(10, 4000);
+    let (idx, choice) = nearly.smart_sort_indexed(|&t| t);
+    println!("Nearly sorted f64s sorted by {choice}");
+    assert_eq!(choice, SortChoice::Adaptive);
+    assert_eq!(idx, nearly.mergesort_indexed());
+    let ru = ranv_u8(5000).expect("ranv_u8 failed").iter().map(|&x| x % 8).collect::<Vec<u8>>();
+    let (idx, choice) = ru.smart_sort_indexed(|&t| t as f64);
+    println!("Repetitive u8s sorted by {choice}");
+    assert_eq!(choice, SortChoice::Merge);
+    assert_eq!(idx, ru.mergesort_indexed());
+    let (short, choice) = ru[..20].smart_sort(|&t| t as f64, false);
+    println!("Short u8s sorted by {choice}:\n{}", short.gr());
+    assert_eq!(choice, SortChoice::Insert);
+}
+
+#[test]
+fn robusthash() {
+    set_seeds(11);
+    let mut rf = ranv_f64(3000).expect("ranv_f64 failed");
+    // outliers, infinities, and a tight cluster
+    rf[0] = 1e12;
+    rf[1] = f64::INFINITY;
+    rf[2] = f64::NEG_INFINITY;
+    rf[3] = -1e12;
+    for x in rf.iter_mut().skip(1000).take(1000) {
+        *x = 0.5 + *x * 1e-12;
+    }
+    let idx = rf.hashsort_robust_indexed(|&t| t);
+    let sorted = idx.unindex(&rf, true);
+    assert!(sorted.sortedness().is_sorted());
+    assert_eq!(sorted[0], f64::NEG_INFINITY);
+    assert_eq!(sorted[2999], f64::INFINITY);
+    assert!(rf.hashsort_indexed(|&t| t).unindex(&rf, true).sortedness().is_sorted());
+    // stability: pairs sorted by their first field only
+    let pairs: Vec<(u8, usize)> = ranv_u8(2000)
+        .expect("ranv_u8 failed")
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| (x % 10, i))
+        .collect();
+    let mut mutpairs = pairs.clone();
+    mutpairs.muthashsort_stable(|&(x, _)| x as f64);
+    let mut stdpairs = pairs;
+    stdpairs.sort_by_key(|&(x, _)| x);
+    assert_eq!(mutpairs, stdpairs);
+    let mut mf = rf.clone();
+    mf.muthashsort(|&t| t);
+    assert_eq!(mf, sorted);
+    println!("Robustly sorted extremes: {} .. {}", sorted[..3].gr(), sorted[2997..].gr());
+}
+
+#[test]
+fn collations() {
+    let files = ["file10.txt", "File9.txt", "file9a.txt", "Émile", "emile", "file009.txt", "zeta", "Eve"];
+    let radix = radixsort(&files, true);
+    println!("Radix sorted:\n{}", radix.gr());
+    assert_eq!(radix, files.sortm(true));
+    let owned: Vec<String> = files.iter().map(|s| s.to_string()).collect();
+    assert_eq!(radixsort_indexed(&owned), files.mergesort_indexed());
+    assert_eq!(radixrank(&files, false), files.rank(false));
+    let nat = files.isort_indexed(0..files.len(), collation(true, true, true)).unindex(&files, true);
+    println!("Caseless, natural, accentless:\n{}", nat.gr());
+    assert_eq!(
+        nat,
+        ["emile", "Émile", "Eve", "File9.txt", "file009.txt", "file9a.txt", "file10.txt", "zeta"]
+    );
+    let best = files.best_k(2, 0..files.len(), natural());
+    println!("Best two in natural order: {}", best.gr());
+    assert_eq!(best, [&"Eve", &"File9.txt"]);
+    let caseless_sorted = files.isort_refs(0..files.len(), caseless()).into_iter().copied().collect::<Vec<&str>>();
+    println!("Caseless: {}", caseless_sorted.gr());
+    // searches find the case and accent variants
+    assert_eq!(caseless_sorted.binsearch_by(&"FILE10.TXT", collation_eq(true, false, false)), 3..4);
+    assert_eq!(caseless_sorted.binsearch_by(&"file10.txt", collation_eq(true, false, false)), 3..4);
+    assert_eq!(caseless_sorted.binsearch_by(&"EMILE", collation_eq(true, false, false)), 0..1);
+    let accidx = files.isort_indexed(0..files.len(), accentless());
+    assert_eq!(files.binsearch_indexed_by(&accidx, &"Emile", collation_eq(false, false, true)), 0..1);
+    assert_eq!(files[accidx[0]], "Émile");
+    let natidx = files.isort_indexed(0..files.len(), collation(true, true, true));
+    assert_eq!(files.binsearch_indexed_by(&natidx, &"FILE9.TXT", collation_eq(true, true, true)), 3..5);
+    // longer data, to exercise the radix buckets
+    set_seeds(13);
+    let words: Vec<String> = ranv_u8(3000)
+        .expect("ranv_u8 failed")
+        .chunks(3)
+        .map(|c| c.iter().map(|&b| (b'a' + b % 4) as char).collect())
+        .collect();
+    assert_eq!(radixsort_indexed(&words), words.mergesort_indexed());
+}
+
+#[test]
+fn matrix() {
+    let m: Vec<Vec<u8>> = vec![
+        vec![3, 1, 2],
+        vec![1, 5],
+        vec![3, 1, 2],
+        vec![1, 5, 0, 9],
+        vec![],
+        vec![2, 2, 2],
+    ];
+    println!("Ragged matrix:\n{}", m.gr());
+    assert_eq!((m.nrows(), m.ncols()), (6, 4));
+    let rowsidx = m.sortrows_indexed();
+    println!("Lexicographic rows order: {}", rowsidx.gr());
+    assert_eq!(rowsidx, [4, 1, 3, 5, 0, 2]);
+    assert_eq!(m.dedup_rows(), [0, 1, 3, 4, 5]);
+    let byrows = m.argsort(Axis::Row);
+    let bycols = m.argsort(Axis::Col);
+    println!("Argsort along rows:\n{}\nArgsort along columns:\n{}", byrows.gr(), bycols.gr());
+    assert_eq!(byrows[0], [1, 2, 0]);
+    assert_eq!(bycols[1], [0, 2, 5, 1, 3]);
+    assert_eq!(bycols[3], [3]);
+    let colranks = m.ranks(Axis::Col, true);
+    println!("Column ranks:\n{}", colranks.gr());
+    assert_eq!(colranks[0], [3, 0, 4, 1, 2]);
+    assert_eq!(m.ranks(Axis::Row, false)[3], [2, 1, 3, 0]);
+    let mm = m.rowsminmax();
+    assert!(mm[4].is_none());
+    println!("Minmax of row 3: {}", mm[3].as_ref().expect("row 3 is not empty"));
+    let slices: Vec<&[u8]> = m.iter().map(|r| r.as_slice()).collect();
+    assert_eq!(slices.sortrows_indexed(), rowsidx);
+}
+
+#[test]
+fn corrmatrix() {
+    let vars: Vec<Vec<f64>> = vec![
+        vec![1., 2., 3., 4., 5., 6.],
+        vec![2., 4., 5., 4., 9., 12.],
+        vec![6., 5., 4., 3., 2., 1.],
+        vec![1., 1., 2., 2., 3., 1.],
+    ];
+    let pearson = vars.correlations(Correlation::Pearson, |&x| x);
+    let spearman = vars.correlations(Correlation::Spearman, |&x| x);
+    let kendall = vars.correlations(Correlation::Kendall, |&x| x);
+    println!("Pearson:\n{}\nSpearman:\n{}\nKendall:\n{}", pearson.gr(), spearman.gr(), kendall.gr());
+    for m in [&pearson, &spearman, &kendall] {
+        assert!((m[0][2] + 1.0).abs() < 1e-12);
+        assert!((m[1][1] - 1.0).abs() < 1e-12);
+        assert_eq!(m[1][3], m[3][1]);
+    }
+    assert!((pearson[0][1] - 0.914_285_7).abs() < 1e-6);
+    // ties: average ranks of var 1 are 1,2.5,4,2.5,5,6
+    assert!((spearman[0][1] - 0.898_645_1).abs() < 1e-6);
+    // tau-b of vars 0,1: 13 concordant, 1 discordant, one tie in the second
+    assert!((kendall[0][1] - 12.0 / (15_f64 * 14.0).sqrt()).abs() < 1e-12);
+    // ties in both variables
+    assert!((kendall[1][3] - 0.322_329_2).abs() < 1e-6);
+    // Spearman on u8 data agrees with ucorrelation of ranks when there are no ties
+    let data: Vec<Vec<u8>> = vec![vec![5, 1, 9, 3, 7], vec![2, 8, 6, 4, 10]];
+    let sp = data.correlations(Correlation::Spearman, |&x| x as f64);
+    let r = data[0].rank(true).ucorrelation(&data[1].rank(true));
+    assert!((sp[0][1] - r).abs() < 1e-12);
+    // long data, whose pair counts overflow usize products
+    let n = 100_000_u32;
+    let long = [(0..n).collect::<Vec<u32>>(), (0..n).map(|x| (x * 7) % n).collect()];
+    let tau = long.correlations(Correlation::Kendall, |&x| x as f64);
+    assert!(tau[0][0] > 0.9999999 && tau[0][1].abs() < 1.0);
+    // fewer than two observations
+    for kind in [Correlation::Pearson, Correlation::Spearman, Correlation::Kendall] {
+        for rows in [vec![vec![], vec![]], vec![vec![1_u8], vec![2]]] {
+            assert!(rows.correlations(kind, |&x| x as f64).iter().flatten().all(|r| r.is_nan()));
+        }
+    }
+}
+
+#[test]
+fn correlations() {
+    let x: [u8; 8] = [3, 1, 4, 1, 5, 9, 2, 6];
+    let y: [u8; 8] = [2, 7, 1, 8, 2, 8, 1, 8];
+    let z: [u8; 8] = [1, 4, 1, 4, 2, 1, 3, 5];
+    let w = [1., 2., 1., 0.5, 1., 3., 1., 1.];
+    println!(
+        "covariance: {GR}{}{UN}, correlation: {GR}{}{UN}, weighted: {GR}{}{UN}, partial: {GR}{}{UN}",
+        x.covariance(&y),
+        x.correlation(&y),
+        x.wcorrelation(&y, &w),
+        x.partial_correlation(&y, &z)
+    );
+    assert!((x.covariance(&y) - 1.946_428_571_428_571).abs() < 1e-12);
+    assert!((x.correlation(&y) - 0.209_655_319_073_012).abs() < 1e-12);
+    assert!((x.wcorrelation(&y, &w) - 0.401_166_991_981_523).abs() < 1e-12);
+    assert!((x.partial_correlation(&y, &z) - 0.534_700_634_023_072).abs() < 1e-12);
+    assert!((x.wcorrelation(&y, &[1.; 8]) - x.correlation(&y)).abs() < 1e-12);
+    // large offsets defeat the naive sums of products
+    let big: Vec<f64> = (0..10000).map(|i| 1e9 + (i % 100) as f64).collect();
+    let twice: Vec<f64> = big.iter().map(|b| 2. * b).collect();
+    assert!((big.correlation(&twice) - 1.0).abs() < 1e-9);
+    let ranks = x.rank(true);
+    assert!((ranks.ucorrelation(&y.rank(true)) - ranks.indx_to_f64().correlation(&y.rank(true).indx_to_f64())).abs() < 1e-12);
+}
+
+#[test]
+fn minmaxstats() {
+    let v: Vec<u8> = vec![5, 2, 9, 2, 7, 9, 3, 2, 9, 4];
+    let all: MinMaxStats<u8> = v.iter().copied().collect();
+    println!("Whole stream: {all}");
+    assert_eq!((all.count, all.min, all.max), (10, Some(2), Some(9)));
+    assert_eq!((all.minfirst, all.minlast, all.maxfirst, all.maxlast), (1, 7, 2, 8));
+    let mm = all.minmax().expect("not empty");
+    let onceoff = v.minmax();
+    assert_eq!((mm.min, mm.minindex, mm.max, mm.maxindex), (onceoff.min, onceoff.minindex, onceoff.max, onceoff.maxindex));
+    // per chunk accumulators, merged
+    let mut merged = MinMaxStats::with_sum(|&x: &u8| x as f64);
+    for chunk in v.chunks(3) {
+        let mut part = MinMaxStats::with_sum(|&x: &u8| x as f64);
+        part.extend(chunk.iter().copied());
+        merged.merge(&part);
+    }
+    println!("Merged chunks: {merged}");
+    assert_eq!((merged.minfirst, merged.minlast, merged.maxfirst, merged.maxlast), (1, 7, 2, 8));
+    assert_eq!(merged.sum, Some(52.0));
+    assert_eq!(merged.range(), Some(7.0));
+    assert_eq!(merged.mean(), Some(5.2));
+    let empty: MinMaxStats<u8> = MinMaxStats::new();
+    assert!(empty.minmax().is_none());
+    merged.merge(&empty);
+    assert_eq!(merged.count, 10);
+    assert_eq!(merged.sum, Some(52.0));
+    let mut fresh: MinMaxStats<u8> = MinMaxStats::new();
+    fresh.merge(&merged);
+    assert_eq!((fresh.count, fresh.sum, fresh.mean()), (10, Some(52.0), Some(5.2)));
+    // NaNs are skipped, as by minmax
+    let f = [1.0_f64, f64::NAN, 0.9];
+    let fstats: MinMaxStats<f64> = f.iter().copied().collect();
+    let mm = f.minmax();
+    assert_eq!((fstats.min, fstats.max), (Some(mm.min), Some(mm.max)));
+    assert_eq!((fstats.minfirst, fstats.maxfirst), (mm.minindex, mm.maxindex));
+    let mut one: MinMaxStats<f64> = [1.0].into_iter().collect();
+    one.merge(&[f64::NAN, f64::NAN].into_iter().collect());
+    assert_eq!((one.count, one.min, one.max), (3, Some(1.0), Some(1.0)));
+}
+
+#[test]
+fn windows() {
+    set_seeds(17);
+    let v = ranv_u8(500).expect("ranv_u8 failed");
+    let w = 37;
+    let slides = v.sliding_minmax(w);
+    let medians = v.rolling_median(w, |&x| x as f64);
+    let ranks = v.rolling_rank(w);
+    assert_eq!((slides.len(), medians.len(), ranks.len()), (464, 464, 464));
+    for (j, mm) in slides.iter().enumerate() {
+        let once = v.minmax_slice(j, w);
+        assert_eq!((mm.min, mm.minindex, mm.max, mm.maxindex), (once.min, once.minindex, once.max, once.maxindex));
+        let mut window = v[j..j + w].to_vec();
+        window.sort_unstable();
+        assert_eq!(medians[j], window[w / 2] as f64);
+        let newest = v[j + w - 1];
+        assert_eq!(ranks[j], window.iter().filter(|&&x| x < newest).count());
+    }
+    let even = [1_u8, 5, 3, 8, 2, 2];
+    println!("Rolling medians of {}: {}", even.gr(), even.rolling_median(4, |&x| x as f64).gr());
+    assert_eq!(even.rolling_median(4, |&x| x as f64), [4., 4., 2.5]);
+    assert_eq!(even.rolling_rank(2), [1, 0, 1, 0, 0]);
+    println!("Last window: {}", slides[463]);
+    assert!(even.sliding_minmax(7).is_empty());
+}
+
+#[test]
+fn rangequeries() {
+    set_seeds(19);
+    let v = ranv_u8(300).expect("ranv_u8 failed");
+    let table = SparseTable::new(&v);
+    let mut seg = SegTree::new(&v);
+    for (i, j) in [(0, 300), (5, 6), (17, 250), (100, 164), (299, 300), (3, 131)] {
+        let once = v.minmax_slice(i, j - i);
+        for mm in [table.minmax(i..j), seg.minmax(i..j)] {
+            assert_eq!((mm.min, mm.minindex, mm.max, mm.maxindex), (once.min, once.minindex, once.max, once.maxindex));
+        }
+        assert_eq!(table.argmin(i..j), once.minindex);
+    }
+    println!("Sparse table minmax over 17..250: {}", table.minmax(17..250));
+    seg.update(42, 255);
+    seg.update(43, 0);
+    let mm = seg.minmax(40..50);
+    println!("Segment tree minmax over 40..50 after updates: {mm}");
+    assert_eq!((mm.min, mm.max), (0, 255));
+    let once = seg.data().minmax_slice(0, 300);
+    assert_eq!(seg.minmax(0..300).minindex, once.minindex);
+}
+
+#[test]
+fn ranktree() {
+    let scores: Vec<u8> = vec![50, 20, 70, 20, 90, 40, 70];
+    let mut board = RankTree::from_sortindex(&scores, &scores.mergesort_indexed());
+    println!("Domain: {}", board.domain().gr());
+    assert_eq!((board.len(), board.rank(&70), board.count(&20)), (7, 4, 2));
+    assert_eq!(board.kth(0), Some(&20));
+    assert_eq!(board.kth(3), Some(&50));
+    assert_eq!(board.kth(7), None);
+    assert_eq!(board.count_between(&20, &70), 4);
+    assert_eq!(board.rank(&45), 3); // not in the domain
+    // online changes: one score of 20 improves to 90
+    assert!(board.remove(&20));
+    assert!(board.insert(&90));
+    assert!(!board.insert(&60)); // not in the domain
+    assert!(!RankTree::with_domain(&scores).remove(&50));
+    let place = board.len() - board.rank(&90) - board.count(&90);
+    println!("Leaderboard place of 90: {place}, median: {}", board.kth(board.len() / 2).expect("not empty"));
+    assert_eq!(place, 0);
+    let mut now = scores.clone();
+    now[1] = 90;
+    let ranks = now.rank(true);
+    for (i, x) in now.iter().enumerate() {
+        // static ranks agree with the dynamic ones, ties ranked in data order
+        assert_eq!(board.rank(x) + now[..i].iter().filter(|&y| y == x).count(), ranks[i]);
+    }
+}
+
+#[test]
+fn subsequences() {
+    let v: Vec<u8> = vec![3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5, 8, 9, 7, 9];
+    let lis = v.lis_indexed(|a, b| a.cmp(b));
+    let lnds = v.lnds_indexed(|a, b| a.cmp(b));
+    let lds = v.lis_indexed(|a, b| b.cmp(a));
+    println!("LIS: {}\nLNDS: {}\nLDS: {}", lis.unindex(&v, true).gr(), lnds.unindex(&v, true).gr(), lds.unindex(&v, true).gr());
+    assert_eq!((lis.len(), lnds.len(), lds.len()), (6, 8, 4));
+    for (sub, strict) in [(&lis, true), (&lnds, false)] {
+        assert!(sub.windows(2).all(|w| w[0] < w[1]));
+        assert!(sub.windows(2).all(|w| v[w[0]] < v[w[1]] || (!strict && v[w[0]] == v[w[1]])));
+    }
+    let piles = v.patience_piles(|a, b| a.cmp(b));
+    println!("Patience piles: {}", piles.gr());
+    assert_eq!(piles.len(), lds.len());
+    assert!(piles.iter().all(|p| p.windows(2).all(|w| v[w[0]] <= v[w[1]])));
+    assert_eq!(piles.iter().map(|p| p.len()).sum::<usize>(), v.len());
+    // agreement of two rankings: LIS of the second ranks in the first ranking's order
+    let first: Vec<u8> = vec![10, 20, 30, 40, 50];
+    let second: Vec<u8> = vec![1, 3, 2, 5, 4];
+    let agree = first.mergesort_indexed().unindex(&second.rank(true), true).lis_indexed(|a, b| a.cmp(b));
+    assert_eq!(agree.len(), 3);
+    assert!(Vec::<u8>::new().lis_indexed(|a, b| a.cmp(b)).is_empty());
+}
+
+#[test]
+fn sampling() {
+    let perm = random_permutation(20, 42);
+    println!("Random permutation: {}", perm.gr());
+    assert_eq!(perm, random_permutation(20, 42));
+    assert_ne!(perm, random_permutation(20, 43));
+    assert_eq!(perm.invindex().invindex(), perm);
+    let mut sorted = perm.clone();
+    sorted.sort_unstable();
+    assert_eq!(sorted, <&[usize]>::newindex(20));
+    let mut v: Vec<u8> = (0..20).collect();
+    shuffle(&mut v, 42);
+    assert_eq!(v.iter().map(|&x| x as usize).collect::<Vec<usize>>(), perm);
+    let sample = sample_indices(1000, 10, 7);
+    println!("Sampled indices: {}", sample.gr());
+    assert_eq!(sample.len(), 10);
+    assert!(sample.windows(2).all(|w| w[0] < w[1]) && sample[9] < 1000);
+    assert_eq!(reservoir("abc".chars(), 5, 1), vec!['a', 'b', 'c']);
+    // uniformity: each item of 0..10 is sampled about 3000 times in 10000 samples of 3
+    let mut counts = [0_usize; 10];
+    for seed in 0..10000 {
+        reservoir(0..10, 3, seed).iter().for_each(|&i| counts[i] += 1);
+    }
+    println!("Reservoir counts: {}", counts.gr());
+    assert!(counts.iter().all(|&c| (2700..3300).contains(&c)));
+    let grades = [b'b', b'a', b'c', b'a', b'b', b'a', b'c', b'b', b'a', b'a'];
+    let strata = stratified_sample(&grades, 2, 5);
+    for (grade, subs) in &strata {
+        println!("Stratum {}: {}", *grade as char, subs.gr());
+        assert!(subs.len() == 2 && subs.iter().all(|&i| grades[i] == *grade));
+    }
+    assert_eq!(strata.len(), 3);
+    let mut rng = Rng::new(3);
+    assert!((0..100).all(|_| rng.below(7) < 7));
+}
+
+#[test]
+fn permutations() {
+    let mut p = <&[usize]>::newindex(4);
+    let mut rank = 0_u128;
+    loop {
+        assert_eq!(p.permrank(), rank);
+        assert_eq!(<&[usize]>::permunrank(4, rank), p);
+        assert_eq!(<&[usize]>::from_lehmer(&p.lehmer()), p);
+        rank += 1;
+        if !p.next_permutation() {
+            break;
+        };
+    }
+    assert_eq!((rank, p.clone()), (24, <&[usize]>::newindex(4)));
+    assert!(!p.prev_permutation());
+    assert_eq!(p, vec![3, 2, 1, 0]);
+    assert!(p.prev_permutation());
+    assert_eq!(p, vec![3, 2, 0, 1]);
+    // repeated items: 4!/2! distinct arrangements
+    let mut w = ['a', 'b', 'b', 'c'];
+    let mut count = 1;
+    while w.next_permutation() {
+        count += 1;
+    }
+    assert_eq!(count, 12);
+    let perm = [2, 0, 3, 1];
+    println!("Lehmer code of {}: {}, rank: {}", perm.gr(), perm.lehmer().gr(), perm.permrank());
+    assert_eq!(perm.lehmer(), vec![2, 0, 1, 0]);
+    assert_eq!(perm.permrank(), 13);
+    assert_eq!(<&[usize]>::factoradic(463, 6), vec![3, 4, 1, 0, 1, 0]);
+    assert_eq!(<&[usize]>::from_factoradic(&[3, 4, 1, 0, 1, 0]), 463);
+    // the last permutation of 34 items has the greatest rank that fits u128
+    let last = <&[usize]>::newindex(34).complindex();
+    let maxrank = last.permrank();
+    assert_eq!(maxrank + 1, (1..=34_u128).product::<u128>());
+    assert_eq!(<&[usize]>::permunrank(34, maxrank), last);
+    // not permutations
+    assert!(std::panic::catch_unwind(|| [1_usize, 1].lehmer()).is_err());
+    assert!(std::panic::catch_unwind(|| [0_usize, 2, 0].permrank()).is_err());
+    assert!(std::panic::catch_unwind(|| [0_usize, 3, 1].lehmer()).is_err());
+}
+
+#[test]
+fn bykeys() {
+    struct Player {
+        name: &'static str,
+        team: u8,
+        score: f64,
+    }
+    let players = [
+        Player { name: "Ann", team: 2, score: 7.5 },
+        Player { name: "Bob", team: 1, score: 9.0 },
+        Player { name: "Cid", team: 2, score: 3.25 },
+        Player { name: "Dee", team: 1, score: 9.0 },
+        Player { name: "Eve", team: 3, score: 5.0 },
+    ];
+    // by team, then by descending score, via tuple key
+    let idx = players.mergesort_indexed_by_key(|p| (p.team, -p.score));
+    let names: Vec<&str> = idx.iter().map(|&i| players[i].name).collect();
+    println!("By team and score: {}", names.gr());
+    assert_eq!(names, ["Bob", "Dee", "Ann", "Cid", "Eve"]);
+    let hidx = players.hashsort_indexed_by_key(|p| p.score);
+    assert_eq!(hidx, players.mergesort_indexed_by_key(|p| p.score));
+    let ranks = players.rank_by_key(|p| p.score, false);
+    println!("Descending score ranks: {}", ranks.gr());
+    assert_eq!(ranks, vec![2, 1, 4, 0, 3]);
+    let teams = players.mergesort_indexed_by_key(|p| p.team);
+    assert_eq!(players.binsearch_indexed_by_key(&teams, &2, |p| p.team), 2..4);
+    assert_eq!(players.binsearch_indexed_by_key(&teams, &0, |p| p.team), 0..0);
+    // tuples of data
+    let pairs = [(3, 'c'), (1, 'z'), (3, 'a'), (2, 'b')];
+    let byletter = pairs.mergesort_indexed_by_key(|&(_, c)| c);
+    assert_eq!(byletter, vec![2, 3, 0, 1]);
+    assert_eq!(pairs.binsearch_indexed_by_key(&byletter, &'c', |&(_, c)| c), 2..3);
+    assert!(Vec::<(u8, u8)>::new().mergesort_indexed_by_key(|&(a, _)| a).is_empty());
+}
+
+#[test]
+fn joins() {
+    let left = [1, 2, 2, 4];
+    let right = [2, 2, 3, 4, 5];
+    let inner = left.join(&right, Join::Inner);
+    println!("Inner join: {inner:?}");
+    assert_eq!(
+        inner,
+        vec![
+            (Some(1), Some(0)),
+            (Some(1), Some(1)),
+            (Some(2), Some(0)),
+            (Some(2), Some(1)),
+            (Some(3), Some(3))
+        ]
+    );
+    let leftj = left.join(&right, Join::Left);
+    assert_eq!(leftj.len(), 6);
+    assert_eq!(leftj[0], (Some(0), None));
+    let rightj = left.join(&right, Join::Right);
+    assert_eq!(rightj.len(), 7);
+    assert!(rightj.contains(&(None, Some(2))) && rightj.contains(&(None, Some(4))));
+    assert_eq!(left.join(&right, Join::Full).len(), 8);
+    assert_eq!(left.join(&right, Join::Semi), vec![(Some(1), None), (Some(2), None), (Some(3), None)]);
+    assert_eq!(left.join(&right, Join::Anti), vec![(Some(0), None)]);
+    // unsorted tables joined via their sort indices
+    let orders = ["bob", "ann", "bob", "cid"];
+    let customers = ["cid", "bob", "dee", "ann"];
+    let (ix1, ix2) = (orders.mergesort_indexed(), customers.mergesort_indexed());
+    let joined = orders.join_indexed(&ix1, &customers, &ix2, Join::Full);
+    println!("Full join via indices: {joined:?}");
+    assert_eq!(
+        joined,
+        vec![
+            (Some(1), Some(3)),
+            (Some(0), Some(1)),
+            (Some(2), Some(1)),
+            (Some(3), Some(0)),
+            (None, Some(2))
+        ]
+    );
+    for (o, c) in orders.join_indexed(&ix1, &customers, &ix2, Join::Inner) {
+        assert_eq!(orders[o.unwrap()], customers[c.unwrap()]);
+    }
+    assert!(<&[u8]>::join(&[], &[1], Join::Inner).is_empty());
+}
+
 #[test]
 fn best_k_sorts() {
     const NAMES: [&str; 2] = [
@@ -465,3 +1089,263 @@ fn small_exhaustive_binary_all() {
         test_array(&array);
     }
 }
+
+#[test]
+fn incremental() {
+    set_seeds(7777777777_u64);
+    let mut v = ranv_u8(20).expect("ranv_u8 failed");
+    let mut idx = v.mergesort_indexed();
+    let mut ranks = idx.invindex();
+    println!("{GR}\nData: {}", v.bl());
+    v.push(128);
+    v.append_indexed(&mut idx, Some(&mut ranks));
+    println!("Appended {BL}128{UN}:   {}", idx.gr());
+    v.extend_from_slice(&[0, 255, 128]);
+    v.append_indexed(&mut idx, Some(&mut ranks));
+    println!("Appended batch:  {}", idx.gr());
+    assert_eq!(idx, v.mergesort_indexed());
+    v[3] = 200;
+    v.update_indexed(&mut idx, 3, Some(&mut ranks));
+    v[7] = 1;
+    v.update_indexed(&mut idx, 7, Some(&mut ranks));
+    println!("Updated v[3],v[7]: {}", idx.gr());
+    assert_eq!(idx, v.mergesort_indexed());
+    v.remove(5);
+    v.remove_indexed(&mut idx, 5, Some(&mut ranks));
+    println!("Removed v[5]:    {}", idx.gr());
+    assert_eq!(idx, v.mergesort_indexed());
+    println!("Ranks:           {}", ranks.gr());
+    assert_eq!(ranks, idx.invindex());
+    // ties: the batch and the single item paths both keep the data order
+    let mut w = vec![5_u8, 5, 1];
+    let mut widx = w.mergesort_indexed();
+    w.extend_from_slice(&[5, 0]);
+    w.append_indexed(&mut widx, None);
+    assert_eq!(widx, [4, 2, 0, 1, 3]);
+    w.push(5);
+    w.append_indexed(&mut widx, None);
+    assert_eq!(widx, [4, 2, 0, 1, 3, 5]);
+}
+
+#[test]
+fn multikeys() {
+    let country = ["UK", "CZ", "UK", "DE", "CZ", "UK", "DE"];
+    let revenue = [300_u32, 150, 500, 150, 150, 300, 700];
+    let id = [7_usize, 3, 1, 6, 2, 4, 5];
+    let kcountry = KeyCol::new(&country, true, |a: &&str, b: &&str| a.cmp(b));
+    let krevenue = KeyCol::new(&revenue, false, |a: &u32, b: &u32| a.cmp(b));
+    let kid = KeyCol::new(&id, true, |a: &usize, b: &usize| a.cmp(b));
+    let keys: [&dyn Key; 3] = [&kcountry, &krevenue, &kid];
+    let idx = multisort_indexed(&keys);
+    println!("\n(country asc, revenue desc, id asc) sort index: {}", idx.gr());
+    for &row in &idx {
+        println!("{}", (country[row], revenue[row], id[row]).to_plainstr().yl());
+    }
+    assert_eq!(idx, [4, 1, 6, 3, 2, 5, 0]);
+    println!("Ranks: {}", multirank(&keys, true).gr());
+    let (uk, r300) = ("UK", 300_u32);
+    let found = multi_binsearch(&idx, &[&kcountry.probe(&uk), &krevenue.probe(&r300)]);
+    println!("Rows with key {}: {}", ("UK", 300).gr(), found.clone().map(|p| idx[p]).collect::<Vec<usize>>().gr());
+    assert_eq!(found, 5..7);
+    let de = "DE";
+    assert_eq!(multi_binsearch(&idx, &[&kcountry.probe(&de)]), 2..4);
+}
+
+#[test]
+fn groups() {
+    let v = [3_u8, 3, 1, 1, 1, 3, 2, 2];
+    println!("{GR}\nData: {}", v.bl());
+    let runs = v.group_runs();
+    println!("Runs (value,start,len):");
+    runs.iter().for_each(|r| print!("{} ", r.yl()));
+    println!();
+    assert_eq!(decode_runs(&runs), v);
+    let groups = v.group_by_index(&v.mergesort_indexed());
+    println!("Groups via sort index:");
+    groups.iter().for_each(|(val, pos)| println!("{}: {}", val.yl(), pos.gr()));
+    assert_eq!(groups[2], (3, vec![0, 1, 5]));
+    let counts = v.value_counts(true);
+    println!("Value counts by frequency:");
+    counts.iter().for_each(|c| print!("{} ", c.gr()));
+    println!();
+    assert_eq!(counts, [(1, 3), (3, 3), (2, 2)]);
+}
+
+#[test]
+fn setops() {
+    let v1 = [1_u8, 2, 2, 2, 3, 5, 5];
+    let v2 = [2_u8, 2, 4, 5, 5, 5];
+    println!("{GR}\nv1: {}\nv2: {}", v1.bl(), v2.bl());
+    println!("Union:        {} multiset: {}", v1.union(&v2, false).gr(), v1.union(&v2, true).gr());
+    println!("Intersection: {} multiset: {}", v1.intersection(&v2, false).gr(), v1.intersection(&v2, true).gr());
+    println!("Difference:   {} multiset: {}", v1.difference(&v2, false).gr(), v1.difference(&v2, true).gr());
+    println!("Symdiff:      {} multiset: {}", v1.symdiff(&v2, false).gr(), v1.symdiff(&v2, true).gr());
+    println!("Sum:          {}", v1.setop(&v2, |c1, c2| c1 + c2).gr());
+    assert_eq!(v1.union(&v2, true), [1, 2, 2, 2, 3, 4, 5, 5, 5]);
+    assert_eq!(v1.intersection(&v2, true), [2, 2, 5, 5]);
+    assert_eq!(v1.difference(&v2, false), [1, 3]);
+    assert_eq!(v1.difference(&v2, true), [1, 2, 3]);
+    assert_eq!(v1.symdiff(&v2, false), [1, 3, 4]);
+    assert_eq!(v1.symdiff(&v2, true), [1, 2, 3, 4, 5]);
+    assert_eq!(v1.setop(&v2, |c1, c2| c1 + c2), v1.merge(&v2));
+    assert!([2_u8, 5].is_subset(&v2, false));
+    assert!(!v1.is_subset(&v2, false));
+    assert!(!v1.is_disjoint(&v2));
+    assert!([1_u8, 3].is_disjoint(&v2));
+    println!("Jaccard: {} multiset: {}", v1.jaccard(&v2, false).gr(), v1.jaccard(&v2, true).gr());
+    assert_eq!(v1.jaccard(&v2, false), 0.4);
+    let u1 = [5_u8, 2, 3, 2, 5, 1, 2];
+    let u2 = [5_u8, 4, 5, 2, 5, 2];
+    let (ix1, ix2) = (u1.mergesort_indexed(), u2.mergesort_indexed());
+    assert_eq!(u1.symdiff_indexed(&ix1, &u2, &ix2, true), v1.symdiff(&v2, true));
+    assert_eq!(u1.jaccard_indexed(&ix1, &u2, &ix2, true), v1.jaccard(&v2, true));
+    assert!(!u1.is_subset_indexed(&ix1, &u2, &ix2, false));
+}
+
+#[test]
+fn setops_many() {
+    let l0: &[u16] = &[1, 3, 5, 7, 9, 11, 13, 15, 17, 19, 21, 23];
+    let l1: &[u16] = &[2, 3, 5, 8, 13, 21];
+    let l2: &[u16] = &[3, 4, 5, 13, 13, 20, 21, 22, 23, 24, 25, 26, 27, 28];
+    let lists = [l0, l1, l2];
+    println!("{GR}\nLists:{}", lists.as_slice().to_str());
+    let common = <&[u16]>::intersect_many(&lists);
+    println!("Intersection (item, positions):");
+    common.iter().for_each(|(item, pos)| print!("{} {} ", item.yl(), pos.gr()));
+    println!();
+    assert_eq!(
+        common.iter().map(|(&item, _)| item).collect::<Vec<u16>>(),
+        [3, 5, 13, 21]
+    );
+    assert_eq!(common[2].1, [6, 4, 3]);
+    let union = <&[u16]>::unite_many(&lists);
+    assert_eq!(union.len(), 22);
+    assert_eq!(union[9], (&13, vec![(0, 6), (1, 4), (2, 3), (2, 4)]));
+    let atleast2 = <&[u16]>::threshold_many(&lists, 2);
+    println!("In at least two lists:");
+    atleast2.iter().for_each(|(item, occ)| print!("{} {} ", item.yl(), occ.len().gr()));
+    println!();
+    assert_eq!(
+        atleast2.iter().map(|(&item, _)| item).collect::<Vec<u16>>(),
+        [3, 5, 13, 21, 23]
+    );
+    // galloping agrees with two-way intersections
+    let v: Vec<u16> = (0..1000).map(|x| x * 3).collect();
+    let w: Vec<u16> = (0..200).map(|x| x * 7).collect();
+    let pair = <&[u16]>::intersect_many(&[&v, &w]);
+    assert_eq!(
+        pair.iter().map(|(&item, _)| item).collect::<Vec<u16>>(),
+        v.intersection(&w, false)
+    );
+}
+
+#[test]
+fn lazy() {
+    set_seeds(4444444444_u64);
+    let v1 = ranv_u8(20).expect("ranv_u8 failed");
+    let v2 = ranv_u8(20).expect("ranv_u8 failed");
+    let (s1, s2) = (v1.sortm(true), v2.sortm(true));
+    let (ix1, ix2) = (v1.mergesort_indexed(), v2.mergesort_indexed());
+    println!("{GR}\ns1: {}\ns2: {}", s1.bl(), s2.bl());
+    let merged: Vec<u8> = s1.merge_iter(&s2).copied().collect();
+    println!("Lazy merge:     {}", merged.gr());
+    assert_eq!(merged, s1.merge(&s2));
+    assert_eq!(s1.intersect_iter(&s2).copied().collect::<Vec<u8>>(), s1.intersect(&s2));
+    assert_eq!(s1.diff_iter(&s2).copied().collect::<Vec<u8>>(), s1.diff(&s2));
+    assert_eq!(
+        v1.unite_indexed_iter(&ix1, &v2, &ix2).copied().collect::<Vec<u8>>(),
+        v1.unite_indexed(&ix1, &v2, &ix2)
+    );
+    assert_eq!(
+        v1.intersect_indexed_iter(&ix1, &v2, &ix2).copied().collect::<Vec<u8>>(),
+        v1.intersect_indexed(&ix1, &v2, &ix2)
+    );
+    assert_eq!(
+        v1.diff_indexed_iter(&ix1, &v2, &ix2).copied().collect::<Vec<u8>>(),
+        v1.diff_indexed(&ix1, &v2, &ix2)
+    );
+    println!(
+        "First merged item over {BL}128{UN}: {}",
+        s1.merge_iter(&s2).find(|&&x| x > 128).map_or_else(|| "None".rd(), |x| x.gr())
+    );
+    let (lt, _, gt) = v1.partition(&128);
+    assert_eq!(v1.partition_iter(&128, Less).count(), lt.len());
+    assert_eq!(v1.partition_iter(&128, Greater).copied().collect::<Vec<u8>>(), gt);
+    assert_eq!(
+        v1.partition_indexed_iter(&128, Less).collect::<Vec<usize>>(),
+        v1.partition_indexed(&128).0
+    );
+    println!("Items less than {BL}128{UN}: {}", v1.partition_iter(&128, Less).count().gr());
+}
+
+#[test]
+fn refs() {
+    // bulky end type that can not be cloned
+    #[derive(PartialEq, PartialOrd, Debug)]
+    struct Bulky(u8, [u64; 16]);
+    let b = |x: u8| Bulky(x, [x as u64; 16]);
+    let v1: Vec<Bulky> = [5_u8, 1, 1, 3, 7, 3].into_iter().map(b).collect();
+    let v2: Vec<Bulky> = [1_u8, 3, 4, 9].into_iter().map(b).collect();
+    let keys = |refs: &[&Bulky]| refs.iter().map(|r| r.0).collect::<Vec<u8>>();
+    let idx1 = v1.isort_indexed(0..v1.len(), |a, b| a.partial_cmp(b).unwrap());
+    let idx2 = <&[usize]>::newindex(v2.len());
+    let s1 = idx1.unindex_refs(&v1, true);
+    println!("\nSorted refs:  {}", keys(&s1).gr());
+    assert_eq!(keys(&s1), [1, 1, 3, 3, 5, 7]);
+    assert_eq!(keys(&idx1.unindex_refs(&v1, false)), [7, 5, 3, 3, 1, 1]);
+    assert_eq!(keys(&v1.revs_refs()), [3, 7, 3, 1, 1, 5]);
+    assert_eq!(keys(&v1.sansrepeat_refs()), [5, 1, 3, 7, 3]);
+    assert_eq!(keys(&v1.sortm_refs(true)), [1, 1, 3, 3, 5, 7]);
+    assert_eq!(keys(&v1.sortm_refs(false)), [7, 5, 3, 3, 1, 1]);
+    let (lt, eq, gt) = v1.partition_refs(&b(3));
+    assert_eq!((keys(&lt), keys(&eq), keys(&gt)), (vec![1, 1], vec![3, 3], vec![5, 7]));
+    assert_eq!(keys(&v1.unite_indexed_refs(&idx1, &v2, &idx2)), [1, 1, 3, 3, 4, 5, 7, 9]);
+    assert_eq!(keys(&v1.intersect_indexed_refs(&idx1, &v2, &idx2)), [1, 3]);
+    assert_eq!(keys(&v1.diff_indexed_refs(&idx1, &v2, &idx2)), [1, 3, 5, 7]);
+    assert_eq!(keys(&v1.setop_indexed_refs(&idx1, &v2, &idx2, |c1, c2| c1 + c2)), [1, 1, 1, 3, 3, 3, 4, 5, 7, 9]);
+    // explicitly sorted slices
+    let (u1, u2) = ([1_u8, 1, 3, 3, 5, 7], [1_u8, 3, 4, 9]);
+    assert_eq!(u1.merge_refs(&u2).into_iter().copied().collect::<Vec<u8>>(), u1.merge(&u2));
+    assert_eq!(u1.intersect_refs(&u2).into_iter().copied().collect::<Vec<u8>>(), u1.intersect(&u2));
+    assert_eq!(u1.diff_refs(&u2).into_iter().copied().collect::<Vec<u8>>(), u1.diff(&u2));
+    assert_eq!(u1.symdiff_refs(&u2, false).into_iter().copied().collect::<Vec<u8>>(), u1.symdiff(&u2, false));
+    assert_eq!(u1.union_refs(&u2, true).len(), 8);
+    assert_eq!(u1.sortm_refs(false).into_iter().copied().collect::<Vec<u8>>(), u1.sortm(false));
+    println!("Union refs:   {}", u1.union_refs(&u2, true).to_str().gr());
+}
+
+#[test]
+fn partitions() {
+    set_seeds(987654321_u64);
+    let v = ranv_u8(30).expect("ranv_u8 failed");
+    println!("{GR}\nData: {}", v.bl());
+    let n = v.len();
+    for pivot in [Pivot::MedianOf3, Pivot::Ninther, Pivot::Random(1234), Pivot::Value(128)] {
+        let mut vm = v.clone();
+        let (ltend, gtstart) = vm.mutpartition(&(0..n), &pivot, &mut |a, b| a.cmp(b));
+        println!(
+            "{} {} {}",
+            vm[..ltend].gr(),
+            vm[ltend..gtstart].yl(),
+            vm[gtstart..].gr()
+        );
+        assert!(vm[..ltend].iter().all(|x| vm[ltend..].iter().all(|y| x < y)));
+        assert!(vm[gtstart..].iter().all(|x| vm[..gtstart].iter().all(|y| x > y)));
+        assert_eq!(vm.sortm(true), v.sortm(true));
+    }
+    // stable partition of (key, position) pairs by key only
+    let mut pairs: Vec<(u8, usize)> = v.iter().map(|&x| x / 64).zip(0..n).collect();
+    let (ltend, gtstart) =
+        pairs.mutpartition_stable(&(0..n), &Pivot::Value((2, 0)), &mut |a, b| a.0.cmp(&b.0));
+    for part in [&pairs[..ltend], &pairs[ltend..gtstart], &pairs[gtstart..]] {
+        assert!(part.windows(2).all(|w| w[0].1 < w[1].1));
+    }
+    // multi-pivot partition into four buckets
+    let mut vm = v.clone();
+    let bounds = vm.mutbuckets(&(0..n), &[64, 128, 192], &mut |a, b| a.cmp(b));
+    println!("Buckets bounds: {}", bounds.gr());
+    for (j, w) in bounds.windows(2).enumerate() {
+        println!("{}", vm[w[0]..w[1]].yl());
+        assert!(vm[w[0]..w[1]].iter().all(|&x| x as usize / 64 == j));
+    }
+}